@@ -1,10 +1,166 @@
 use anchor_lang::prelude::*;
+use anchor_spl::associated_token::AssociatedToken;
+use anchor_spl::token::{self, FreezeAccount, Mint, MintTo, Token, TokenAccount, Transfer};
 
 declare_id!("38CFzCb11EneZMQujTVZqJmXU7mXLxMg9fsS9hSZgnsC");
 
+// Risk flag bits for `SafetyReport::flags` (mirrors the off-chain SafetyCategory)
+pub const FLAG_TOKEN_CONCENTRATION: u8 = 0;
+pub const FLAG_MINT_AUTHORITY: u8 = 1;      // Mint authority not renounced
+pub const FLAG_FREEZE_AUTHORITY: u8 = 2;    // Freeze authority not renounced
+pub const FLAG_ACCOUNT_AGE: u8 = 3;         // Token or deployer is very new
+pub const FLAG_LOW_VOLUME: u8 = 4;
+pub const FLAG_KNOWN_SCAM: u8 = 5;
+pub const FLAG_UNVERIFIED_CONTRACT: u8 = 6;
+pub const FLAG_SUSPICIOUS_PATTERN: u8 = 7;
+pub const FLAG_AGENTSHIELD: u8 = 8;         // Flagged by AgentShield
+/// Bits above the last defined flag are reserved and must be zero.
+pub const VALID_FLAGS_MASK: u32 = (1 << 9) - 1;
+
+/// Direction of `SafetyReport::risk_score`, reported by get_conventions.
+pub const SCORE_HIGHER_IS_SAFER: u8 = 0;
+pub const SCORE_HIGHER_IS_RISKIER: u8 = 1;
+/// The convention this program uses: 100 = safest, 0 = riskiest.
+pub const SCORE_CONVENTION: u8 = SCORE_HIGHER_IS_SAFER;
+pub const MAX_RISK_SCORE: u8 = 100;
+
+/// get_verdict thresholds of a new registry: score 70+ and RiskLevel::Low.
+pub const DEFAULT_SAFE_SCORE_MIN: u8 = 70;
+pub const DEFAULT_SAFE_LEVEL_MIN: u8 = RiskLevel::Low as u8;
+
+/// Layout version written to Registry, SafetyReport, SubscriptionConfig and
+/// Subscription accounts. Mutating instructions reject older accounts.
+/// `version` is each account's first field, right after the discriminator, so
+/// a migrator can read it from any layout. Every later change to one of these
+/// layouts must bump this and append its fields after `bump`, so that an older
+/// account is a prefix of the current layout:
+///
+/// 1. `version` added
+/// 2. SafetyReport `resolved`
+/// 3. SubscriptionConfig per-tier vectors replace the basic/pro/alpha fields
+/// 4. Registry `min_update_interval`
+/// 5. SafetyReport `active`
+/// 6. Registry `signers`, `threshold`
+/// 7. SubscriptionConfig `refund_window`, `refund_mode`
+/// 8. SafetyReport `category`
+/// 9. SubscriptionConfig `max_subscribers`
+/// 10. SubscriptionConfig `promo_prices`, `promo_ends_at`
+/// 11. Registry `flag_penalty_threshold`, `max_score_with_flags`
+/// 12. SubscriptionConfig `report_quotas`, `report_quota_period`
+/// 13. SubscriptionConfig `whitelist_root`
+/// 14. SubscriptionConfig `annual_discount_bps`
+/// 15. Subscription `receipt_mint`
+/// 16. SubscriptionConfig `last_admin_action`, `inactivity_timeout`
+/// 17. SafetyReport `finalized`
+/// 18. SubscriptionConfig `token_treasury`
+/// 19. SafetyReport `report_slot`
+/// 20. Subscription `first_subscribed_at`, `total_active_seconds`, `active_since`
+/// 21. SubscriptionConfig `late_penalty_bps`
+/// 22. SafetyReport `cosigner`
+/// 23. SubscriptionConfig `subscribe_cooldown`
+/// 24. Registry `safe_score_min`, `safe_level_min`
+/// 25. SubscriptionConfig `revenue_split_set`
+pub const CURRENT_VERSION: u8 = 25;
+
+/// Reputation given to a reporter on their first report.
+pub const DEFAULT_REPUTATION: u16 = 100;
+pub const MAX_REPUTATION: u16 = 10_000;
+
+/// SafetyReport confidence before any endorsement or dispute (neutral).
+pub const DEFAULT_CONFIDENCE: u8 = 50;
+
+/// Max SafetyReport protocol_name length in bytes (must match its `max_len`).
+pub const MAX_PROTOCOL_NAME_LEN: usize = 64;
+
+/// Reports one authority can keep per token via submit_report's `report_slot` (0..MAX_REPORT_SLOTS).
+pub const MAX_REPORT_SLOTS: u8 = 8;
+
+/// Versions kept per ReportHistory (must match its `max_len`).
+pub const REPORT_HISTORY_LEN: usize = 16;
+
+/// Entries kept per SubscriptionHistory (must match its `max_len`).
+pub const SUBSCRIPTION_HISTORY_LEN: usize = 16;
+
+/// SubscriptionHistory entry actions.
+pub const SUB_ACTION_SUBSCRIBE: u8 = 0;
+pub const SUB_ACTION_RENEW: u8 = 1;
+pub const SUB_ACTION_UPGRADE: u8 = 2;
+pub const SUB_ACTION_DOWNGRADE: u8 = 3; // Scheduled for the current expiry
+pub const SUB_ACTION_CANCEL: u8 = 4;    // `price` is the refund
+pub const SUB_ACTION_TOPUP: u8 = 5;
+
+/// TreasuryInflow reasons.
+pub const INFLOW_SUBSCRIBE: u8 = 0;
+pub const INFLOW_RENEW: u8 = 1;
+pub const INFLOW_ANNUAL: u8 = 2;
+pub const INFLOW_GIFT: u8 = 3;
+pub const INFLOW_LIFETIME: u8 = 4;
+pub const INFLOW_AUTO_RENEW: u8 = 5;    // crank_renew, paid from escrow
+pub const INFLOW_TOPUP: u8 = 6;
+
+/// Max days bought by one topup_subscription call.
+pub const MAX_TOPUP_DAYS: u32 = 365;
+
+/// Mints kept per FlagIndex (must match its `max_len`).
+pub const MAX_FLAG_INDEX_LEN: usize = 64;
+
+/// Authorities kept per TokenReportIndex (must match its `max_len`).
+pub const MAX_TOKEN_INDEX_LEN: usize = 32;
+
+/// Mints kept per Watchlist (must match its `max_len`).
+pub const MAX_WATCHLIST_LEN: usize = 32;
+
+/// Cycles bought at once by subscribe_annual.
+pub const ANNUAL_CYCLES: u64 = 12;
+
+/// Free trial length set at config initialization (7 days).
+pub const DEFAULT_TRIAL_DURATION: i64 = 7 * 24 * 60 * 60;
+
+/// Limits for ReportFlags (must match its `max_len`).
+pub const MAX_FLAG_REASONS: usize = 8;
+pub const MAX_FLAG_REASON_LEN: usize = 48;
+
+/// Max members of a registry's multisig signer set (must match its `max_len`).
+pub const MAX_REGISTRY_SIGNERS: usize = 5;
+
+/// Report categories (SafetyReport.category), by protocol type.
+pub const CATEGORY_UNKNOWN: u8 = 0;
+pub const CATEGORY_DEX: u8 = 1;
+pub const CATEGORY_LENDING: u8 = 2;
+pub const CATEGORY_MEMECOIN: u8 = 3;
+pub const CATEGORY_NFT: u8 = 4;
+pub const CATEGORY_BRIDGE: u8 = 5;
+
+/// Default length of a reporter's quota period (see SubscriptionConfig.report_quotas).
+pub const DEFAULT_REPORT_QUOTA_PERIOD: i64 = 24 * 60 * 60;
+
+/// Max reports per submit_reports_batch call, to stay within compute limits.
+pub const MAX_BATCH_SIZE: usize = 10;
+
+/// Max subscription tiers (must match the `max_len` of the SubscriptionConfig tier vectors).
+pub const MAX_TIERS: usize = 8;
+
+/// Lowest lamport price update_pricing/initialize_subscription_config accept
+/// for a tier (0.001 SOL), so a mistyped price cannot give subscriptions away.
+pub const MIN_TIER_PRICE: u64 = 1_000_000;
+
+/// Max subscriptions per verify_subscriptions_batch call, to fit the return data limit.
+pub const MAX_VERIFY_BATCH: usize = 25;
+
+/// Recipients per RevenueSplit (must match its `max_len`).
+pub const MAX_SPLIT_RECIPIENTS: usize = 4;
+
+/// Max sibling hashes in a claim_whitelisted proof (trees of up to 2^20 wallets).
+pub const MAX_MERKLE_PROOF_LEN: usize = 20;
+
+/// cancel_subscription refund modes (SubscriptionConfig.refund_mode).
+pub const REFUND_MODE_PRORATED: u8 = 0;         // Unused time refunded at any point
+pub const REFUND_MODE_WINDOW: u8 = 1;           // Full refund within refund_window, none after
+pub const REFUND_MODE_WINDOW_PRORATED: u8 = 2;  // Full refund within refund_window, prorated after
+
 /// AirdropAlpha Registry Program
 /// - Safety analysis reports on-chain
-/// - User subscription management (SOL payments for MVP)
+/// - User subscription management (SOL or SPL token payments)
 /// - Tiered access control
 #[program]
 pub mod airdrop_registry {
@@ -19,6 +175,19 @@ pub mod airdrop_registry {
         let registry = &mut ctx.accounts.registry;
         registry.authority = ctx.accounts.authority.key();
         registry.total_reports = 0;
+        registry.paused = false;
+        registry.min_reputation = 0;
+        registry.max_report_age = 0;
+        registry.min_update_interval = 0;
+        registry.flag_penalty_threshold = 0;
+        registry.max_score_with_flags = 100;
+        registry.signers = Vec::new();
+        registry.threshold = 0;
+        registry.safe_score_min = DEFAULT_SAFE_SCORE_MIN;
+        registry.safe_level_min = DEFAULT_SAFE_LEVEL_MIN;
+        registry.pending_authority = None;
+        registry.previous_authority = None;
+        registry.version = CURRENT_VERSION;
         registry.bump = ctx.bumps.registry;
 
         msg!("Registry initialized for authority: {}", ctx.accounts.authority.key());
@@ -26,445 +195,7956 @@ pub mod airdrop_registry {
     }
 
     /// Submit a new safety analysis report for a token.
-    pub fn submit_report(
-        ctx: Context<SubmitReport>,
+    /// A HIGH report needs a `cosigner` from the registry's `signers`, which
+    /// starts empty, so HIGH reports are impossible until set_registry_signers
+    /// adds one.
+    /// remaining_accounts: the FlagIndex for each set bit of `flags`, in ascending bit order.
+    #[allow(clippy::too_many_arguments)]
+    pub fn submit_report<'info>(
+        ctx: Context<'_, '_, 'info, 'info, SubmitReport<'info>>,
         protocol_name: String,
         risk_score: u8,
-        risk_level: u8,
-        flags_count: u8,
+        risk_level: RiskLevel,
+        flags: u32,         // Bitmask of FLAG_* risk categories
+        report_uri: String, // Off-chain detailed report (IPFS/Arweave); empty if none
+        min_tier: u8,       // Subscription tier needed to read it via read_gated_report; 0 = public
+        category: u8,       // CATEGORY_* protocol type
+        report_slot: u8,    // Distinct report of this authority for the mint; 0 = default
     ) -> Result<()> {
-        require!(risk_score <= 100, ErrorCode::InvalidRiskScore);
-        require!(risk_level <= 2, ErrorCode::InvalidRiskLevel);
-        require!(protocol_name.len() <= 32, ErrorCode::ProtocolNameTooLong);
+        check_version(ctx.accounts.registry.version)?;
+        require!(!ctx.accounts.registry.paused, ErrorCode::RegistryPaused);
+        require!(ctx.accounts.registry.threshold == 0, ErrorCode::MultisigRequired);
+        require!(report_slot < MAX_REPORT_SLOTS, ErrorCode::InvalidReportSlot);
+        validate_report_fields(&protocol_name, risk_score, flags, &report_uri, category)?;
+        ctx.accounts.registry.check_consistency(risk_score, flags)?;
+        require!(min_tier as usize <= MAX_TIERS, ErrorCode::InvalidTier);
+        let cosigner = ctx.accounts.cosigner.as_ref().map(|c| c.key());
+        ctx.accounts.registry.check_cosigner(ctx.accounts.authority.key(), risk_level, cosigner)?;
+
+        let now = Clock::get()?.unix_timestamp;
+        let config = &ctx.accounts.subscription_config;
+        let quota = config.report_quota(ctx.accounts.subscription.as_deref(), now);
+        let reporter = &mut ctx.accounts.reporter;
+        reporter.init_if_new(ctx.accounts.authority.key(), ctx.bumps.reporter);
+        require!(
+            reporter.reputation >= ctx.accounts.registry.min_reputation,
+            ErrorCode::InsufficientReputation
+        );
+        reporter.consume_quota(1, quota, config.report_quota_period, now)?;
 
         let report = &mut ctx.accounts.safety_report;
         report.authority = ctx.accounts.authority.key();
         report.token_mint = ctx.accounts.token_mint.key();
         report.risk_score = risk_score;
         report.risk_level = risk_level;
-        report.flags_count = flags_count;
-        report.protocol_name = protocol_name.clone();
+        report.flags = flags;
+        report.flags_count = flags.count_ones() as u8;
+        report.category = category;
+        report.protocol_name = protocol_name;
+        report.report_uri = report_uri;
         report.timestamp = Clock::get()?.unix_timestamp;
+        report.created_at = report.timestamp;
+        report.endorsements = 0;
+        report.disputes = 0;
+        report.confidence = DEFAULT_CONFIDENCE;
+        report.min_tier = min_tier;
+        report.resolved = false;
+        report.active = true;
+        report.finalized = false;
+        report.report_slot = report_slot;
+        report.cosigner = cosigner;
+        report.version = CURRENT_VERSION;
         report.bump = ctx.bumps.safety_report;
+        update_flag_indexes(ctx.remaining_accounts, report.token_mint, 0, flags)?;
+
+        let stats = &mut ctx.accounts.global_stats;
+        stats.bump = ctx.bumps.global_stats;
+        stats.add(risk_score, risk_level)?;
+
+        let consensus = &mut ctx.accounts.consensus;
+        consensus.init_if_new(report.token_mint, ctx.bumps.consensus);
+        if report.in_consensus() {
+            consensus.add(risk_score, risk_level)?;
+        }
+
+        let token_index = &mut ctx.accounts.token_index;
+        token_index.init_if_new(report.token_mint, ctx.bumps.token_index);
+        token_index.insert(report.authority, report_slot);
 
         let registry = &mut ctx.accounts.registry;
-        registry.total_reports = registry.total_reports.checked_add(1).unwrap();
+        registry.total_reports = registry.total_reports.checked_add(1).ok_or(ErrorCode::ArithmeticOverflow)?;
 
-        msg!("Safety report submitted: {} | score: {} | level: {} | flags: {}",
-            protocol_name, risk_score, risk_level, flags_count);
+        let reporter = &mut ctx.accounts.reporter;
+        reporter.reports_submitted = reporter.reports_submitted.checked_add(1).ok_or(ErrorCode::ArithmeticOverflow)?;
+        reporter.active_reports = reporter.active_reports.checked_add(1).ok_or(ErrorCode::ArithmeticOverflow)?;
+
+        emit!(ReportSubmitted {
+            report: report.key(),
+            token_mint: report.token_mint,
+            authority: report.authority,
+            risk_score,
+            risk_level,
+            flags,
+            flags_count: report.flags_count,
+            category,
+            created_at: report.created_at,
+            timestamp: report.timestamp,
+        });
+
+        msg!("Safety report submitted: {} | score: {} | level: {} | flags: {} | uri: {} | created: {} | updated: {}",
+            report.protocol_name, risk_score, risk_level as u8, report.flags_count, report.report_uri,
+            report.created_at, report.timestamp);
         Ok(())
     }
 
     /// Update an existing safety report.
-    pub fn update_report(
-        ctx: Context<UpdateReport>,
+    /// The report is not resized here: one stored by an older layout must first
+    /// be brought up to date with migrate_report, e.g. earlier in the same transaction.
+    /// remaining_accounts: the FlagIndex for each bit that changes between the old
+    /// and new `flags`, in ascending bit order.
+    pub fn update_report<'info>(
+        ctx: Context<'_, '_, 'info, 'info, UpdateReport<'info>>,
         protocol_name: String,
         risk_score: u8,
-        risk_level: u8,
-        flags_count: u8,
+        risk_level: RiskLevel,
+        flags: u32,         // Bitmask of FLAG_* risk categories
+        report_uri: String, // Off-chain detailed report (IPFS/Arweave); empty if none
+        category: u8,       // CATEGORY_* protocol type
     ) -> Result<()> {
-        require!(risk_score <= 100, ErrorCode::InvalidRiskScore);
-        require!(risk_level <= 2, ErrorCode::InvalidRiskLevel);
-        require!(protocol_name.len() <= 32, ErrorCode::ProtocolNameTooLong);
+        check_version(ctx.accounts.safety_report.version)?;
+        require!(!ctx.accounts.registry.paused, ErrorCode::RegistryPaused);
+        validate_report_fields(&protocol_name, risk_score, flags, &report_uri, category)?;
+        ctx.accounts.registry.check_consistency(risk_score, flags)?;
+        let cosigner = ctx.accounts.cosigner.as_ref().map(|c| c.key());
+        ctx.accounts.registry.check_cosigner(ctx.accounts.authority.key(), risk_level, cosigner)?;
+        let now = Clock::get()?.unix_timestamp;
+        require!(
+            now.saturating_sub(ctx.accounts.safety_report.timestamp) >= ctx.accounts.registry.min_update_interval,
+            ErrorCode::UpdateTooSoon
+        );
 
+        // Archive the pre-update values before overwriting them
         let report = &mut ctx.accounts.safety_report;
+        let history = &mut ctx.accounts.report_history;
+        if history.report == Pubkey::default() {
+            history.report = report.key();
+            history.bump = ctx.bumps.report_history;
+        }
+        let version = history.push(HistoryEntry {
+            risk_score: report.risk_score,
+            risk_level: report.risk_level,
+            flags_count: report.flags_count,
+            timestamp: report.timestamp,
+        });
+
+        update_flag_indexes(ctx.remaining_accounts, report.token_mint, report.flags, flags)?;
+
+        let old_score = report.risk_score;
+        // Hidden reports are not counted (see set_report_active)
+        if report.active {
+            let stats = &mut ctx.accounts.global_stats;
+            stats.remove(old_score, report.risk_level)?;
+            stats.add(risk_score, risk_level)?;
+        }
+        if report.in_consensus() {
+            let consensus = &mut ctx.accounts.consensus;
+            consensus.remove(old_score, report.risk_level)?;
+            consensus.add(risk_score, risk_level)?;
+        }
+
         report.risk_score = risk_score;
         report.risk_level = risk_level;
-        report.flags_count = flags_count;
-        report.protocol_name = protocol_name.clone();
-        report.timestamp = Clock::get()?.unix_timestamp;
+        report.flags = flags;
+        report.flags_count = flags.count_ones() as u8;
+        report.category = category;
+        report.protocol_name = protocol_name;
+        report.report_uri = report_uri;
+        report.cosigner = cosigner;
+        report.timestamp = now;
 
-        msg!("Safety report updated: {} | score: {}", protocol_name, risk_score);
+        emit!(ReportUpdated {
+            report: report.key(),
+            token_mint: report.token_mint,
+            authority: report.authority,
+            old_score,
+            new_score: risk_score,
+            category,
+            created_at: report.created_at,
+            timestamp: report.timestamp,
+        });
+
+        if risk_score != old_score {
+            emit!(RiskChanged {
+                token_mint: report.token_mint,
+                delta: risk_score as i16 - old_score as i16,
+                new_level: risk_level,
+            });
+        }
+        emit!(WatchedTokenUpdated {
+            token_mint: report.token_mint,
+            report: report.key(),
+            risk_score,
+            risk_level,
+            timestamp: now,
+        });
+
+        msg!("Safety report updated: {} | score: {} | uri: {} | version: {} | history: {} | created: {} | updated: {}",
+            report.protocol_name, risk_score, report.report_uri, version, history.entries.len(),
+            report.created_at, report.timestamp);
         Ok(())
     }
 
-    // ========================================================================
-    // Subscription Management (SOL payments for MVP)
-    // ========================================================================
+    /// Change only a report's `risk_score`, leaving its level, flags and other
+    /// fields as they are. The prior values still go to its ReportHistory.
+    pub fn update_risk_score(ctx: Context<UpdateRiskScore>, new_score: u8) -> Result<()> {
+        check_version(ctx.accounts.safety_report.version)?;
+        let registry = &ctx.accounts.registry;
+        require!(!registry.paused, ErrorCode::RegistryPaused);
+        require!(new_score <= 100, ErrorCode::InvalidRiskScore);
+        registry.check_consistency(new_score, ctx.accounts.safety_report.flags)?;
+        let now = Clock::get()?.unix_timestamp;
+        require!(
+            now.saturating_sub(ctx.accounts.safety_report.timestamp) >= registry.min_update_interval,
+            ErrorCode::UpdateTooSoon
+        );
 
-    /// Initialize the subscription config (admin only, once).
-    pub fn initialize_subscription_config(
-        ctx: Context<InitializeSubscriptionConfig>,
-        basic_price_lamports: u64,
-        pro_price_lamports: u64,
-        alpha_price_lamports: u64,
-        subscription_duration: i64, // Duration in seconds
-    ) -> Result<()> {
-        let config = &mut ctx.accounts.subscription_config;
-        config.admin = ctx.accounts.admin.key();
-        config.treasury = ctx.accounts.treasury.key();
-        config.basic_price = basic_price_lamports;
-        config.pro_price = pro_price_lamports;
-        config.alpha_price = alpha_price_lamports;
-        config.subscription_duration = subscription_duration;
-        config.total_subscribers = 0;
-        config.total_revenue = 0;
-        config.bump = ctx.bumps.subscription_config;
+        let report = &mut ctx.accounts.safety_report;
+        let history = &mut ctx.accounts.report_history;
+        if history.report == Pubkey::default() {
+            history.report = report.key();
+            history.bump = ctx.bumps.report_history;
+        }
+        history.push(HistoryEntry {
+            risk_score: report.risk_score,
+            risk_level: report.risk_level,
+            flags_count: report.flags_count,
+            timestamp: report.timestamp,
+        });
 
-        msg!("Subscription config initialized. Treasury: {}", ctx.accounts.treasury.key());
+        let old_score = report.risk_score;
+        if report.active {
+            let stats = &mut ctx.accounts.global_stats;
+            stats.remove(old_score, report.risk_level)?;
+            stats.add(new_score, report.risk_level)?;
+        }
+        if report.in_consensus() {
+            let consensus = &mut ctx.accounts.consensus;
+            consensus.remove(old_score, report.risk_level)?;
+            consensus.add(new_score, report.risk_level)?;
+        }
+
+        report.risk_score = new_score;
+        report.timestamp = now;
+
+        emit!(ReportUpdated {
+            report: report.key(),
+            token_mint: report.token_mint,
+            authority: report.authority,
+            old_score,
+            new_score,
+            category: report.category,
+            created_at: report.created_at,
+            timestamp: now,
+        });
+
+        if new_score != old_score {
+            emit!(RiskChanged {
+                token_mint: report.token_mint,
+                delta: new_score as i16 - old_score as i16,
+                new_level: report.risk_level,
+            });
+        }
+        emit!(WatchedTokenUpdated {
+            token_mint: report.token_mint,
+            report: report.key(),
+            risk_score: new_score,
+            risk_level: report.risk_level,
+            timestamp: now,
+        });
+
+        msg!("Risk score updated: token_mint={} score={} old_score={}",
+            report.token_mint, new_score, old_score);
         Ok(())
     }
 
-    /// Subscribe with SOL payment.
-    /// Tier: 1 = Basic, 2 = Pro, 3 = Alpha
-    pub fn subscribe(ctx: Context<Subscribe>, tier: u8) -> Result<()> {
-        require!(tier >= 1 && tier <= 3, ErrorCode::InvalidTier);
+    /// Create the report for `input.token_mint` or, if it already exists, update
+    /// it as update_report would. Only a creation counts toward total_reports
+    /// and the report quota.
+    /// remaining_accounts: the FlagIndex for each bit that changes between the
+    /// existing (or, on creation, empty) `flags` and `input.flags`, in ascending bit order.
+    pub fn upsert_report<'info>(
+        ctx: Context<'_, '_, 'info, 'info, UpsertReport<'info>>,
+        input: ReportInput,
+    ) -> Result<()> {
+        let registry = &ctx.accounts.registry;
+        require!(!registry.paused, ErrorCode::RegistryPaused);
+        require!(registry.threshold == 0, ErrorCode::MultisigRequired);
+        validate_report_input(&input)?;
+        registry.check_consistency(input.risk_score, input.flags)?;
+        let authority = ctx.accounts.authority.key();
+        let cosigner = ctx.accounts.cosigner.as_ref().map(|c| c.key());
+        registry.check_cosigner(authority, input.risk_level, cosigner)?;
 
-        let config = &ctx.accounts.subscription_config;
-        let price = match tier {
-            1 => config.basic_price,
-            2 => config.pro_price,
-            3 => config.alpha_price,
-            _ => return Err(ErrorCode::InvalidTier.into()),
-        };
+        let now = Clock::get()?.unix_timestamp;
+        let report_key = ctx.accounts.safety_report.key();
+        let is_new = ctx.accounts.safety_report.authority == Pubkey::default();
+        ctx.accounts.global_stats.bump = ctx.bumps.global_stats;
+        ctx.accounts.consensus.init_if_new(input.token_mint, ctx.bumps.consensus);
 
-        // Transfer SOL from user to treasury
-        let ix = anchor_lang::solana_program::system_instruction::transfer(
-            &ctx.accounts.user.key(),
-            &ctx.accounts.treasury.key(),
-            price,
+        if is_new {
+            let config = &ctx.accounts.subscription_config;
+            let quota = config.report_quota(ctx.accounts.subscription.as_deref(), now);
+            let reporter = &mut ctx.accounts.reporter;
+            reporter.init_if_new(authority, ctx.bumps.reporter);
+            require!(
+                reporter.reputation >= registry.min_reputation,
+                ErrorCode::InsufficientReputation
+            );
+            reporter.consume_quota(1, quota, config.report_quota_period, now)?;
+            reporter.reports_submitted = reporter.reports_submitted.checked_add(1).ok_or(ErrorCode::ArithmeticOverflow)?;
+            reporter.active_reports = reporter.active_reports.checked_add(1).ok_or(ErrorCode::ArithmeticOverflow)?;
+
+            let mut report = SafetyReport::new(authority, input, now, ctx.bumps.safety_report);
+            report.cosigner = cosigner;
+            update_flag_indexes(ctx.remaining_accounts, report.token_mint, 0, report.flags)?;
+            ctx.accounts.global_stats.add(report.risk_score, report.risk_level)?;
+            ctx.accounts.consensus.add(report.risk_score, report.risk_level)?;
+            let token_index = &mut ctx.accounts.token_index;
+            token_index.init_if_new(report.token_mint, ctx.bumps.token_index);
+            token_index.insert(authority, report.report_slot);
+            emit!(ReportSubmitted {
+                report: report_key,
+                token_mint: report.token_mint,
+                authority,
+                risk_score: report.risk_score,
+                risk_level: report.risk_level,
+                flags: report.flags,
+                flags_count: report.flags_count,
+                category: report.category,
+                created_at: now,
+                timestamp: now,
+            });
+            ctx.accounts.safety_report.set_inner(report);
+
+            let registry = &mut ctx.accounts.registry;
+            registry.total_reports = registry.total_reports.checked_add(1).ok_or(ErrorCode::ArithmeticOverflow)?;
+
+            msg!("Safety report upserted (created): token_mint={} score={}",
+                ctx.accounts.safety_report.token_mint, ctx.accounts.safety_report.risk_score);
+            return Ok(());
+        }
+
+        let report = &mut ctx.accounts.safety_report;
+        check_version(report.version)?;
+        require!(!report.finalized, ErrorCode::ReportFinalized);
+        require!(
+            now.saturating_sub(report.timestamp) >= registry.min_update_interval,
+            ErrorCode::UpdateTooSoon
         );
-        anchor_lang::solana_program::program::invoke(
-            &ix,
-            &[
-                ctx.accounts.user.to_account_info(),
-                ctx.accounts.treasury.to_account_info(),
-            ],
-        )?;
 
-        // Create subscription
-        let subscription = &mut ctx.accounts.subscription;
-        let clock = Clock::get()?;
-        let now = clock.unix_timestamp;
-        let new_expiry = now.checked_add(config.subscription_duration).unwrap();
+        let history = &mut ctx.accounts.report_history;
+        if history.report == Pubkey::default() {
+            history.report = report_key;
+            history.bump = ctx.bumps.report_history;
+        }
+        history.push(HistoryEntry {
+            risk_score: report.risk_score,
+            risk_level: report.risk_level,
+            flags_count: report.flags_count,
+            timestamp: report.timestamp,
+        });
 
-        subscription.user = ctx.accounts.user.key();
-        subscription.tier = tier;
-        subscription.expires_at = new_expiry;
-        subscription.created_at = now;
-        subscription.total_paid = price;
-        subscription.bump = ctx.bumps.subscription;
+        update_flag_indexes(ctx.remaining_accounts, report.token_mint, report.flags, input.flags)?;
 
-        // Update config stats
-        let config = &mut ctx.accounts.subscription_config;
-        config.total_subscribers = config.total_subscribers.checked_add(1).unwrap();
-        config.total_revenue = config.total_revenue.checked_add(price).unwrap();
+        let old_score = report.risk_score;
+        if report.active {
+            ctx.accounts.global_stats.remove(old_score, report.risk_level)?;
+            ctx.accounts.global_stats.add(input.risk_score, input.risk_level)?;
+        }
+        if report.in_consensus() {
+            ctx.accounts.consensus.remove(old_score, report.risk_level)?;
+            ctx.accounts.consensus.add(input.risk_score, input.risk_level)?;
+        }
+
+        report.risk_score = input.risk_score;
+        report.risk_level = input.risk_level;
+        report.flags = input.flags;
+        report.flags_count = input.flags.count_ones() as u8;
+        report.category = input.category;
+        report.min_tier = input.min_tier;
+        report.protocol_name = input.protocol_name;
+        report.report_uri = input.report_uri;
+        report.cosigner = cosigner;
+        report.timestamp = now;
+
+        emit!(ReportUpdated {
+            report: report_key,
+            token_mint: report.token_mint,
+            authority,
+            old_score,
+            new_score: report.risk_score,
+            category: report.category,
+            created_at: report.created_at,
+            timestamp: now,
+        });
 
-        msg!("Subscription created: user={} tier={} expires={}", 
-            ctx.accounts.user.key(), tier, new_expiry);
+        if report.risk_score != old_score {
+            emit!(RiskChanged {
+                token_mint: report.token_mint,
+                delta: report.risk_score as i16 - old_score as i16,
+                new_level: report.risk_level,
+            });
+        }
+        emit!(WatchedTokenUpdated {
+            token_mint: report.token_mint,
+            report: report_key,
+            risk_score: report.risk_score,
+            risk_level: report.risk_level,
+            timestamp: now,
+        });
+
+        msg!("Safety report upserted (updated): token_mint={} score={} old_score={}",
+            report.token_mint, report.risk_score, old_score);
         Ok(())
     }
 
-    /// Renew or upgrade an existing subscription.
-    pub fn renew_subscription(ctx: Context<RenewSubscription>, tier: u8) -> Result<()> {
-        require!(tier >= 1 && tier <= 3, ErrorCode::InvalidTier);
+    /// Submit up to MAX_BATCH_SIZE new reports in one transaction.
+    /// remaining_accounts: for each input, in order, its (uninitialized) SafetyReport
+    /// PDA, the mint's TokenConsensus PDA and the mint's TokenReportIndex PDA (both
+    /// created if missing); then,
+    /// for each input in order, the FlagIndex for each set bit of its `flags`, in
+    /// ascending bit order.
+    pub fn submit_reports_batch<'info>(
+        ctx: Context<'_, '_, 'info, 'info, SubmitReportsBatch<'info>>,
+        reports: Vec<ReportInput>,
+    ) -> Result<()> {
+        check_version(ctx.accounts.registry.version)?;
+        require!(!ctx.accounts.registry.paused, ErrorCode::RegistryPaused);
+        require!(ctx.accounts.registry.threshold == 0, ErrorCode::MultisigRequired);
+        require!(
+            !reports.is_empty() && reports.len() <= MAX_BATCH_SIZE,
+            ErrorCode::InvalidBatchSize
+        );
+        let flag_accounts_len: usize = reports.iter().map(|r| r.flags.count_ones() as usize).sum();
+        require!(
+            ctx.remaining_accounts.len() == reports.len() * 3 + flag_accounts_len,
+            ErrorCode::InvalidRemainingAccounts
+        );
+        let (report_accounts, flag_accounts) = ctx.remaining_accounts.split_at(reports.len() * 3);
 
+        let authority = ctx.accounts.authority.key();
+        let cosigner = ctx.accounts.cosigner.as_ref().map(|c| c.key());
+        let now = Clock::get()?.unix_timestamp;
+        ctx.accounts.global_stats.bump = ctx.bumps.global_stats;
         let config = &ctx.accounts.subscription_config;
-        let price = match tier {
-            1 => config.basic_price,
-            2 => config.pro_price,
-            3 => config.alpha_price,
-            _ => return Err(ErrorCode::InvalidTier.into()),
-        };
-
-        // Transfer SOL from user to treasury
-        let ix = anchor_lang::solana_program::system_instruction::transfer(
-            &ctx.accounts.user.key(),
-            &ctx.accounts.treasury.key(),
-            price,
+        let quota = config.report_quota(ctx.accounts.subscription.as_deref(), now);
+        let reporter = &mut ctx.accounts.reporter;
+        reporter.init_if_new(authority, ctx.bumps.reporter);
+        require!(
+            reporter.reputation >= ctx.accounts.registry.min_reputation,
+            ErrorCode::InsufficientReputation
         );
-        anchor_lang::solana_program::program::invoke(
-            &ix,
-            &[
-                ctx.accounts.user.to_account_info(),
-                ctx.accounts.treasury.to_account_info(),
-            ],
-        )?;
+        reporter.consume_quota(reports.len() as u64, quota, config.report_quota_period, now)?;
 
-        // Update subscription
-        let subscription = &mut ctx.accounts.subscription;
-        let clock = Clock::get()?;
-        let now = clock.unix_timestamp;
+        // Validate every element before writing any
+        let mut bumps = Vec::with_capacity(reports.len());
+        for (input, accounts) in reports.iter().zip(report_accounts.chunks(3)) {
+            validate_report_input(input)?;
+            ctx.accounts.registry.check_cosigner(authority, input.risk_level, cosigner)?;
+            ctx.accounts.registry.check_consistency(input.risk_score, input.flags)?;
+            let (pda, bump) = Pubkey::find_program_address(
+                &[b"safety_report", input.token_mint.as_ref(), authority.as_ref()],
+                ctx.program_id,
+            );
+            require_keys_eq!(accounts[0].key(), pda, ErrorCode::InvalidReportAccount);
+            require!(accounts[0].lamports() == 0, ErrorCode::ReportAlreadyExists);
+            let (consensus_pda, consensus_bump) = Pubkey::find_program_address(
+                &[b"consensus", input.token_mint.as_ref()],
+                ctx.program_id,
+            );
+            require_keys_eq!(accounts[1].key(), consensus_pda, ErrorCode::InvalidConsensusAccount);
+            let (index_pda, index_bump) = Pubkey::find_program_address(
+                &[b"token_index", input.token_mint.as_ref()],
+                ctx.program_id,
+            );
+            require_keys_eq!(accounts[2].key(), index_pda, ErrorCode::InvalidTokenIndexAccount);
+            bumps.push((bump, consensus_bump, index_bump));
+        }
 
-        let base_time = if subscription.expires_at > now {
-            subscription.expires_at
-        } else {
-            now
-        };
-        let new_expiry = base_time.checked_add(config.subscription_duration).unwrap();
+        let space = 8 + SafetyReport::INIT_SPACE;
+        let lamports = Rent::get()?.minimum_balance(space);
+        let count = reports.len() as u64;
 
-        subscription.tier = tier;
-        subscription.expires_at = new_expiry;
-        subscription.total_paid = subscription.total_paid.checked_add(price).unwrap();
+        let mut flag_offset = 0;
+        for ((input, accounts), (bump, consensus_bump, index_bump)) in reports.into_iter().zip(report_accounts.chunks(3)).zip(bumps) {
+            let info = &accounts[0];
+            let ix = anchor_lang::solana_program::system_instruction::create_account(
+                &authority,
+                info.key,
+                lamports,
+                space as u64,
+                ctx.program_id,
+            );
+            anchor_lang::solana_program::program::invoke_signed(
+                &ix,
+                &[ctx.accounts.authority.to_account_info(), info.clone()],
+                &[&[b"safety_report", input.token_mint.as_ref(), authority.as_ref(), &[bump]]],
+            )?;
 
-        // Update config stats
-        let config = &mut ctx.accounts.subscription_config;
-        config.total_revenue = config.total_revenue.checked_add(price).unwrap();
+            let mut report = SafetyReport::new(authority, input, now, bump);
+            report.cosigner = cosigner;
+            let mut data = info.try_borrow_mut_data()?;
+            report.try_serialize(&mut &mut data[..])?;
+            let flag_count = report.flags.count_ones() as usize;
+            update_flag_indexes(
+                &flag_accounts[flag_offset..flag_offset + flag_count],
+                report.token_mint,
+                0,
+                report.flags,
+            )?;
+            flag_offset += flag_count;
+            ctx.accounts.global_stats.add(report.risk_score, report.risk_level)?;
+            add_to_consensus(
+                &accounts[1],
+                &ctx.accounts.authority.to_account_info(),
+                ctx.program_id,
+                report.token_mint,
+                consensus_bump,
+                report.risk_score,
+                report.risk_level,
+            )?;
+            add_to_token_index(
+                &accounts[2],
+                &ctx.accounts.authority.to_account_info(),
+                ctx.program_id,
+                report.token_mint,
+                index_bump,
+                authority,
+            )?;
+
+            emit!(ReportSubmitted {
+                report: info.key(),
+                token_mint: report.token_mint,
+                authority,
+                risk_score: report.risk_score,
+                risk_level: report.risk_level,
+                flags: report.flags,
+                flags_count: report.flags_count,
+                category: report.category,
+                created_at: now,
+                timestamp: now,
+            });
+        }
 
-        msg!("Subscription renewed: user={} tier={} expires={}", 
-            subscription.user, tier, new_expiry);
+        let registry = &mut ctx.accounts.registry;
+        registry.total_reports = registry.total_reports.checked_add(count).ok_or(ErrorCode::ArithmeticOverflow)?;
+
+        let reporter = &mut ctx.accounts.reporter;
+        reporter.reports_submitted = reporter.reports_submitted.checked_add(count).ok_or(ErrorCode::ArithmeticOverflow)?;
+        reporter.active_reports = reporter.active_reports.checked_add(count).ok_or(ErrorCode::ArithmeticOverflow)?;
+
+        msg!("Safety report batch submitted: {} reports | total: {}", count, registry.total_reports);
         Ok(())
     }
 
-    /// Verify subscription status.
-    pub fn verify_subscription(ctx: Context<VerifySubscription>, required_tier: u8) -> Result<()> {
-        let subscription = &ctx.accounts.subscription;
-        let clock = Clock::get()?;
-        
-        let is_active = subscription.expires_at > clock.unix_timestamp;
-        let has_tier = subscription.tier >= required_tier;
-        let verified = is_active && has_tier;
+    /// Close one of the signer's reports and return its rent.
+    /// remaining_accounts: the FlagIndex for each set bit of the report's flags,
+    /// in ascending bit order, so the mint is dropped from them.
+    pub fn close_report<'info>(ctx: Context<'_, '_, 'info, 'info, CloseReport<'info>>) -> Result<()> {
+        check_version(ctx.accounts.safety_report.version)?;
+        let report = &ctx.accounts.safety_report;
+        update_flag_indexes(ctx.remaining_accounts, report.token_mint, report.flags, 0)?;
+        if report.active {
+            ctx.accounts.global_stats.remove(report.risk_score, report.risk_level)?;
+        }
+        if report.in_consensus() {
+            ctx.accounts.consensus.remove(report.risk_score, report.risk_level)?;
+        }
+        ctx.accounts.token_index.remove(&report.authority, report.report_slot);
 
-        msg!("Subscription verification: user={} tier={} active={} verified={}",
-            subscription.user, subscription.tier, is_active, verified);
-        
-        require!(verified, ErrorCode::InsufficientSubscription);
+        let reporter = &mut ctx.accounts.reporter;
+        reporter.active_reports = reporter.active_reports.checked_sub(1).ok_or(ErrorCode::ArithmeticOverflow)?;
+
+        msg!("Report closed: token_mint={} authority={} active_reports={}",
+            report.token_mint, report.authority, reporter.active_reports);
         Ok(())
     }
 
-    /// Admin: Update subscription pricing.
-    pub fn update_pricing(
-        ctx: Context<UpdatePricing>,
-        basic_price: u64,
-        pro_price: u64,
-        alpha_price: u64,
+    /// Admin: Set a registry's M-of-N signer set. While `threshold` is non-zero,
+    /// new reports must go through submit_report_multisig; with a threshold of 0
+    /// the signers only co-sign HIGH reports in submit_report.
+    pub fn set_registry_signers(
+        ctx: Context<AdminRegistry>,
+        signers: Vec<Pubkey>,
+        threshold: u8,
     ) -> Result<()> {
-        let config = &mut ctx.accounts.subscription_config;
-        config.basic_price = basic_price;
-        config.pro_price = pro_price;
-        config.alpha_price = alpha_price;
+        check_version(ctx.accounts.registry.version)?;
+        ctx.accounts.subscription_config.record_admin_action()?;
+        require!(signers.len() <= MAX_REGISTRY_SIGNERS, ErrorCode::InvalidThreshold);
+        require!(threshold as usize <= signers.len(), ErrorCode::InvalidThreshold);
+        for (i, signer) in signers.iter().enumerate() {
+            require!(!signers[..i].contains(signer), ErrorCode::DuplicateSigner);
+        }
+
+        let registry = &mut ctx.accounts.registry;
+        registry.signers = signers;
+        registry.threshold = threshold;
 
-        msg!("Pricing updated: basic={} pro={} alpha={}", basic_price, pro_price, alpha_price);
+        msg!("Registry signers set: registry={} signers={} threshold={}",
+            registry.authority, registry.signers.len(), threshold);
         Ok(())
     }
-}
 
-// ============================================================================
-// Account Contexts - Registry
-// ============================================================================
+    /// Multisig member: Propose a new report for the registry. The proposal
+    /// counts as the proposer's approval.
+    pub fn submit_report_multisig(ctx: Context<SubmitReportMultisig>, input: ReportInput) -> Result<()> {
+        let registry = &ctx.accounts.registry;
+        check_version(registry.version)?;
+        require!(!registry.paused, ErrorCode::RegistryPaused);
+        require!(registry.threshold > 0, ErrorCode::MultisigNotConfigured);
+        let signer = ctx.accounts.signer.key();
+        require!(registry.signers.contains(&signer), ErrorCode::NotASigner);
+        validate_report_input(&input)?;
+        registry.check_consistency(input.risk_score, input.flags)?;
 
-#[derive(Accounts)]
-pub struct InitializeRegistry<'info> {
-    #[account(
-        init,
-        payer = authority,
-        space = 8 + Registry::INIT_SPACE,
-        seeds = [b"registry", authority.key().as_ref()],
-        bump
-    )]
-    pub registry: Account<'info, Registry>,
+        let pending = &mut ctx.accounts.pending_report;
+        pending.registry = registry.key();
+        pending.proposer = signer;
+        pending.input = input;
+        pending.approvals = vec![signer];
+        pending.created_at = Clock::get()?.unix_timestamp;
+        pending.bump = ctx.bumps.pending_report;
 
-    #[account(mut)]
-    pub authority: Signer<'info>,
+        msg!("Report proposed: registry={} token_mint={} proposer={} approvals=1/{}",
+            registry.authority, pending.input.token_mint, signer, registry.threshold);
+        Ok(())
+    }
 
-    pub system_program: Program<'info, System>,
-}
+    /// Multisig member: Approve a pending report, once per member.
+    pub fn approve_report_multisig(ctx: Context<ApproveReportMultisig>) -> Result<()> {
+        let registry = &ctx.accounts.registry;
+        let signer = ctx.accounts.signer.key();
+        require!(registry.signers.contains(&signer), ErrorCode::NotASigner);
 
-#[derive(Accounts)]
-pub struct SubmitReport<'info> {
-    #[account(
-        init,
-        payer = authority,
-        space = 8 + SafetyReport::INIT_SPACE,
-        seeds = [b"safety_report", token_mint.key().as_ref(), authority.key().as_ref()],
-        bump
-    )]
-    pub safety_report: Account<'info, SafetyReport>,
+        let pending = &mut ctx.accounts.pending_report;
+        require!(!pending.approvals.contains(&signer), ErrorCode::AlreadyApproved);
+        pending.approvals.push(signer);
 
-    #[account(
-        mut,
-        seeds = [b"registry", authority.key().as_ref()],
-        bump = registry.bump,
-        has_one = authority
-    )]
-    pub registry: Account<'info, Registry>,
+        msg!("Report approved: registry={} token_mint={} signer={} approvals={}/{}",
+            registry.authority, pending.input.token_mint, signer,
+            pending.approvals.len(), registry.threshold);
+        Ok(())
+    }
 
-    /// CHECK: Token mint address used as seed.
-    pub token_mint: AccountInfo<'info>,
+    /// Anyone: Write a pending report once approvals from current members reach
+    /// the threshold. The report belongs to the registry authority; the pending
+    /// account's rent returns to its proposer.
+    /// remaining_accounts: the FlagIndex for each set bit of `flags`, in ascending bit order.
+    pub fn execute_report_multisig<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ExecuteReportMultisig<'info>>,
+    ) -> Result<()> {
+        let registry = &ctx.accounts.registry;
+        check_version(registry.version)?;
+        require!(!registry.paused, ErrorCode::RegistryPaused);
+        let pending = &ctx.accounts.pending_report;
+        let approvals = pending.approvals.iter().filter(|k| registry.signers.contains(k)).count();
+        require!(
+            registry.threshold > 0 && approvals >= registry.threshold as usize,
+            ErrorCode::ThresholdNotReached
+        );
 
-    #[account(mut)]
-    pub authority: Signer<'info>,
+        let authority = registry.authority;
+        let now = Clock::get()?.unix_timestamp;
+        let config = &ctx.accounts.subscription_config;
+        let quota = config.report_quota(ctx.accounts.subscription.as_deref(), now);
+        let reporter = &mut ctx.accounts.reporter;
+        reporter.init_if_new(authority, ctx.bumps.reporter);
+        require!(
+            reporter.reputation >= registry.min_reputation,
+            ErrorCode::InsufficientReputation
+        );
+        reporter.consume_quota(1, quota, config.report_quota_period, now)?;
 
-    pub system_program: Program<'info, System>,
-}
+        let report = SafetyReport::new(authority, pending.input.clone(), now, ctx.bumps.safety_report);
+        update_flag_indexes(ctx.remaining_accounts, report.token_mint, 0, report.flags)?;
+        let stats = &mut ctx.accounts.global_stats;
+        stats.bump = ctx.bumps.global_stats;
+        stats.add(report.risk_score, report.risk_level)?;
+        let consensus = &mut ctx.accounts.consensus;
+        consensus.init_if_new(report.token_mint, ctx.bumps.consensus);
+        consensus.add(report.risk_score, report.risk_level)?;
+        let token_index = &mut ctx.accounts.token_index;
+        token_index.init_if_new(report.token_mint, ctx.bumps.token_index);
+        token_index.insert(authority, report.report_slot);
+        emit!(ReportSubmitted {
+            report: ctx.accounts.safety_report.key(),
+            token_mint: report.token_mint,
+            authority,
+            risk_score: report.risk_score,
+            risk_level: report.risk_level,
+            flags: report.flags,
+            flags_count: report.flags_count,
+            category: report.category,
+            created_at: now,
+            timestamp: now,
+        });
+        ctx.accounts.safety_report.set_inner(report);
 
-#[derive(Accounts)]
-pub struct UpdateReport<'info> {
-    #[account(
-        mut,
-        seeds = [b"safety_report", safety_report.token_mint.as_ref(), authority.key().as_ref()],
-        bump = safety_report.bump,
-        has_one = authority
-    )]
-    pub safety_report: Account<'info, SafetyReport>,
+        let registry = &mut ctx.accounts.registry;
+        registry.total_reports = registry.total_reports.checked_add(1).ok_or(ErrorCode::ArithmeticOverflow)?;
 
-    #[account(mut)]
-    pub authority: Signer<'info>,
-}
+        let reporter = &mut ctx.accounts.reporter;
+        reporter.reports_submitted = reporter.reports_submitted.checked_add(1).ok_or(ErrorCode::ArithmeticOverflow)?;
+        reporter.active_reports = reporter.active_reports.checked_add(1).ok_or(ErrorCode::ArithmeticOverflow)?;
 
-// ============================================================================
-// Account Contexts - Subscriptions
-// ============================================================================
+        msg!("Multisig report executed: registry={} token_mint={} approvals={}",
+            authority, ctx.accounts.safety_report.token_mint, approvals);
+        Ok(())
+    }
 
-#[derive(Accounts)]
-pub struct InitializeSubscriptionConfig<'info> {
-    #[account(
-        init,
-        payer = admin,
-        space = 8 + SubscriptionConfig::INIT_SPACE,
-        seeds = [b"subscription_config"],
-        bump
-    )]
-    pub subscription_config: Account<'info, SubscriptionConfig>,
+    /// Let `delegate` submit reports on behalf of the signer's registry, or
+    /// re-enable a revoked delegate.
+    pub fn add_delegate(ctx: Context<AddDelegate>, delegate: Pubkey) -> Result<()> {
+        check_version(ctx.accounts.registry.version)?;
+        require_keys_neq!(delegate, Pubkey::default(), ErrorCode::InvalidRecipient);
 
-    /// CHECK: Treasury account to receive SOL payments.
-    #[account(mut)]
-    pub treasury: AccountInfo<'info>,
+        let entry = &mut ctx.accounts.delegate_account;
+        entry.authority = ctx.accounts.authority.key();
+        entry.delegate = delegate;
+        entry.revoked = false;
+        entry.created_at = Clock::get()?.unix_timestamp;
+        entry.bump = ctx.bumps.delegate_account;
 
-    #[account(mut)]
-    pub admin: Signer<'info>,
+        msg!("Delegate added: authority={} delegate={}", entry.authority, delegate);
+        Ok(())
+    }
 
-    pub system_program: Program<'info, System>,
-}
+    /// Stop a delegate from submitting further reports. Its existing reports stay.
+    pub fn revoke_delegate(ctx: Context<RevokeDelegate>) -> Result<()> {
+        let entry = &mut ctx.accounts.delegate_account;
+        entry.revoked = true;
 
-#[derive(Accounts)]
-pub struct Subscribe<'info> {
-    #[account(
-        init,
-        payer = user,
-        space = 8 + Subscription::INIT_SPACE,
-        seeds = [b"subscription", user.key().as_ref()],
-        bump
-    )]
-    pub subscription: Account<'info, Subscription>,
+        msg!("Delegate revoked: authority={} delegate={}", entry.authority, entry.delegate);
+        Ok(())
+    }
 
-    #[account(
-        mut,
-        seeds = [b"subscription_config"],
-        bump = subscription_config.bump
-    )]
-    pub subscription_config: Account<'info, SubscriptionConfig>,
+    /// Delegate: Submit a new report attributed to the registry authority.
+    /// The delegate pays the rent. Not available while the registry requires multisig.
+    /// remaining_accounts: the FlagIndex for each set bit of `flags`, in ascending bit order.
+    pub fn submit_report_delegated<'info>(
+        ctx: Context<'_, '_, 'info, 'info, SubmitReportDelegated<'info>>,
+        input: ReportInput,
+    ) -> Result<()> {
+        let registry = &ctx.accounts.registry;
+        check_version(registry.version)?;
+        require!(!registry.paused, ErrorCode::RegistryPaused);
+        require!(registry.threshold == 0, ErrorCode::MultisigRequired);
+        validate_report_input(&input)?;
+        // The cosigner must differ from the delegate, who is the one submitting
+        let cosigner = ctx.accounts.cosigner.as_ref().map(|c| c.key());
+        registry.check_cosigner(ctx.accounts.delegate.key(), input.risk_level, cosigner)?;
+        registry.check_consistency(input.risk_score, input.flags)?;
 
-    /// CHECK: Treasury to receive payment.
-    #[account(
-        mut,
-        constraint = treasury.key() == subscription_config.treasury @ ErrorCode::InvalidTreasury
-    )]
-    pub treasury: AccountInfo<'info>,
+        let authority = registry.authority;
+        let now = Clock::get()?.unix_timestamp;
+        let config = &ctx.accounts.subscription_config;
+        let quota = config.report_quota(ctx.accounts.subscription.as_deref(), now);
+        let reporter = &mut ctx.accounts.reporter;
+        reporter.init_if_new(authority, ctx.bumps.reporter);
+        require!(
+            reporter.reputation >= registry.min_reputation,
+            ErrorCode::InsufficientReputation
+        );
+        reporter.consume_quota(1, quota, config.report_quota_period, now)?;
 
-    #[account(mut)]
-    pub user: Signer<'info>,
+        let mut report = SafetyReport::new(authority, input, now, ctx.bumps.safety_report);
+        report.cosigner = cosigner;
+        update_flag_indexes(ctx.remaining_accounts, report.token_mint, 0, report.flags)?;
+        let stats = &mut ctx.accounts.global_stats;
+        stats.bump = ctx.bumps.global_stats;
+        stats.add(report.risk_score, report.risk_level)?;
+        let consensus = &mut ctx.accounts.consensus;
+        consensus.init_if_new(report.token_mint, ctx.bumps.consensus);
+        consensus.add(report.risk_score, report.risk_level)?;
+        let token_index = &mut ctx.accounts.token_index;
+        token_index.init_if_new(report.token_mint, ctx.bumps.token_index);
+        token_index.insert(authority, report.report_slot);
+        emit!(ReportSubmitted {
+            report: ctx.accounts.safety_report.key(),
+            token_mint: report.token_mint,
+            authority,
+            risk_score: report.risk_score,
+            risk_level: report.risk_level,
+            flags: report.flags,
+            flags_count: report.flags_count,
+            category: report.category,
+            created_at: now,
+            timestamp: now,
+        });
+        ctx.accounts.safety_report.set_inner(report);
 
-    pub system_program: Program<'info, System>,
-}
+        let registry = &mut ctx.accounts.registry;
+        registry.total_reports = registry.total_reports.checked_add(1).ok_or(ErrorCode::ArithmeticOverflow)?;
 
-#[derive(Accounts)]
-pub struct RenewSubscription<'info> {
-    #[account(
-        mut,
-        seeds = [b"subscription", user.key().as_ref()],
+        let reporter = &mut ctx.accounts.reporter;
+        reporter.reports_submitted = reporter.reports_submitted.checked_add(1).ok_or(ErrorCode::ArithmeticOverflow)?;
+        reporter.active_reports = reporter.active_reports.checked_add(1).ok_or(ErrorCode::ArithmeticOverflow)?;
+
+        msg!("Delegated report submitted: authority={} delegate={} token_mint={}",
+            authority, ctx.accounts.delegate.key(), ctx.accounts.safety_report.token_mint);
+        Ok(())
+    }
+
+    /// Submit a new report without writing the registry, so concurrent
+    /// submissions don't contend for its lock. The report is counted in the
+    /// reporter's `unsynced_reports` and only reaches `registry.total_reports`
+    /// at the next sync_report_count.
+    /// remaining_accounts: the FlagIndex for each set bit of `flags`, in ascending bit order.
+    pub fn submit_report_lite<'info>(
+        ctx: Context<'_, '_, 'info, 'info, SubmitReportLite<'info>>,
+        input: ReportInput,
+    ) -> Result<()> {
+        let registry = &ctx.accounts.registry;
+        check_version(registry.version)?;
+        require!(!registry.paused, ErrorCode::RegistryPaused);
+        require!(registry.threshold == 0, ErrorCode::MultisigRequired);
+        validate_report_input(&input)?;
+        let cosigner = ctx.accounts.cosigner.as_ref().map(|c| c.key());
+        registry.check_cosigner(registry.authority, input.risk_level, cosigner)?;
+        registry.check_consistency(input.risk_score, input.flags)?;
+
+        let authority = registry.authority;
+        let now = Clock::get()?.unix_timestamp;
+        let config = &ctx.accounts.subscription_config;
+        let quota = config.report_quota(ctx.accounts.subscription.as_deref(), now);
+        let reporter = &mut ctx.accounts.reporter;
+        reporter.init_if_new(authority, ctx.bumps.reporter);
+        require!(
+            reporter.reputation >= registry.min_reputation,
+            ErrorCode::InsufficientReputation
+        );
+        reporter.consume_quota(1, quota, config.report_quota_period, now)?;
+
+        let mut report = SafetyReport::new(authority, input, now, ctx.bumps.safety_report);
+        report.cosigner = cosigner;
+        update_flag_indexes(ctx.remaining_accounts, report.token_mint, 0, report.flags)?;
+        let stats = &mut ctx.accounts.global_stats;
+        stats.bump = ctx.bumps.global_stats;
+        stats.add(report.risk_score, report.risk_level)?;
+        let consensus = &mut ctx.accounts.consensus;
+        consensus.init_if_new(report.token_mint, ctx.bumps.consensus);
+        consensus.add(report.risk_score, report.risk_level)?;
+        let token_index = &mut ctx.accounts.token_index;
+        token_index.init_if_new(report.token_mint, ctx.bumps.token_index);
+        token_index.insert(authority, report.report_slot);
+        emit!(ReportSubmitted {
+            report: ctx.accounts.safety_report.key(),
+            token_mint: report.token_mint,
+            authority,
+            risk_score: report.risk_score,
+            risk_level: report.risk_level,
+            flags: report.flags,
+            flags_count: report.flags_count,
+            category: report.category,
+            created_at: now,
+            timestamp: now,
+        });
+        ctx.accounts.safety_report.set_inner(report);
+
+        let reporter = &mut ctx.accounts.reporter;
+        reporter.reports_submitted = reporter.reports_submitted.checked_add(1).ok_or(ErrorCode::ArithmeticOverflow)?;
+        reporter.active_reports = reporter.active_reports.checked_add(1).ok_or(ErrorCode::ArithmeticOverflow)?;
+        reporter.unsynced_reports = reporter.unsynced_reports.checked_add(1).ok_or(ErrorCode::ArithmeticOverflow)?;
+
+        msg!("Lite report submitted: authority={} token_mint={} unsynced={}",
+            authority, ctx.accounts.safety_report.token_mint, reporter.unsynced_reports);
+        Ok(())
+    }
+
+    /// Admin: Add a registry's lite submissions to its `total_reports`.
+    pub fn sync_report_count(ctx: Context<SyncReportCount>) -> Result<()> {
+        check_version(ctx.accounts.registry.version)?;
+        ctx.accounts.subscription_config.record_admin_action()?;
+        let reporter = &mut ctx.accounts.reporter;
+        let synced = reporter.unsynced_reports;
+        reporter.unsynced_reports = 0;
+
+        let registry = &mut ctx.accounts.registry;
+        registry.total_reports = registry.total_reports.checked_add(synced).ok_or(ErrorCode::ArithmeticOverflow)?;
+
+        msg!("Report count synced: registry={} synced={} total_reports={}",
+            registry.authority, synced, registry.total_reports);
+        Ok(())
+    }
+
+    /// Reset a registry's `total_reports` to the number of its reports, healing
+    /// any counter drift. The reports are passed as `remaining_accounts` over one
+    /// or more calls, in ascending address order across all of them, so none can
+    /// be counted twice: `first_page` starts a new count in the ReportRecount,
+    /// and `last_page` writes it to the registry. Closed reports no longer exist,
+    /// so the result counts only reports that are still open.
+    pub fn recompute_total_reports<'info>(
+        ctx: Context<'_, '_, 'info, 'info, RecomputeTotalReports<'info>>,
+        first_page: bool,
+        last_page: bool,
+    ) -> Result<()> {
+        check_version(ctx.accounts.registry.version)?;
+        let authority = ctx.accounts.registry.authority;
+
+        let recount = &mut ctx.accounts.recount;
+        if first_page {
+            recount.registry = ctx.accounts.registry.key();
+            recount.count = 0;
+            recount.last_report = Pubkey::default();
+            recount.in_progress = true;
+            recount.bump = ctx.bumps.recount;
+        }
+        require!(recount.in_progress, ErrorCode::RecountNotStarted);
+
+        for info in ctx.remaining_accounts.iter() {
+            let report: Account<SafetyReport> = Account::try_from(info)?;
+            require_keys_eq!(report.authority, authority, ErrorCode::Unauthorized);
+            require!(info.key() > recount.last_report, ErrorCode::DuplicateReportAccount);
+            recount.last_report = info.key();
+            recount.count = recount.count.checked_add(1).ok_or(ErrorCode::ArithmeticOverflow)?;
+        }
+        if !last_page {
+            msg!("Recount in progress: registry={} counted={}", authority, recount.count);
+            return Ok(());
+        }
+        recount.in_progress = false;
+        let count = recount.count;
+
+        // Every lite submission is now counted directly.
+        ctx.accounts.reporter.unsynced_reports = 0;
+
+        let registry = &mut ctx.accounts.registry;
+        let before = registry.total_reports;
+        registry.total_reports = count;
+
+        emit!(TotalReportsRecomputed {
+            registry: registry.key(),
+            before,
+            after: registry.total_reports,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        msg!("Total reports recomputed: registry={} before={} after={}",
+            authority, before, registry.total_reports);
+        Ok(())
+    }
+
+    /// Pause or resume report submissions and updates for a registry.
+    pub fn set_paused(ctx: Context<SetPaused>, paused: bool) -> Result<()> {
+        check_version(ctx.accounts.registry.version)?;
+        let registry = &mut ctx.accounts.registry;
+        registry.paused = paused;
+
+        msg!("Registry {}: {}", if paused { "paused" } else { "resumed" }, registry.authority);
+        Ok(())
+    }
+
+    /// Propose handing the registry over to `new_authority` (two-step).
+    pub fn propose_registry_authority(
+        ctx: Context<ProposeRegistryAuthority>,
+        new_authority: Pubkey,
+    ) -> Result<()> {
+        check_version(ctx.accounts.registry.version)?;
+        let registry = &mut ctx.accounts.registry;
+        registry.pending_authority = Some(new_authority);
+
+        msg!("Registry authority transfer proposed: current={} pending={}",
+            registry.authority, new_authority);
+        Ok(())
+    }
+
+    /// Accept a proposed registry handover. Registry PDAs are seeded by their
+    /// authority, so this re-creates the registry under the new authority,
+    /// remembering the old one in `previous_authority`, and closes the old registry.
+    /// Reports are moved afterwards, one by one, with migrate_report_authority.
+    pub fn accept_registry_authority(ctx: Context<AcceptRegistryAuthority>) -> Result<()> {
+        check_version(ctx.accounts.old_registry.version)?;
+        let old = &ctx.accounts.old_registry;
+        let registry = &mut ctx.accounts.registry;
+        registry.authority = ctx.accounts.new_authority.key();
+        registry.total_reports = old.total_reports;
+        registry.paused = old.paused;
+        registry.min_reputation = old.min_reputation;
+        registry.max_report_age = old.max_report_age;
+        registry.min_update_interval = old.min_update_interval;
+        registry.flag_penalty_threshold = old.flag_penalty_threshold;
+        registry.max_score_with_flags = old.max_score_with_flags;
+        registry.signers = old.signers.clone();
+        registry.threshold = old.threshold;
+        registry.safe_score_min = old.safe_score_min;
+        registry.safe_level_min = old.safe_level_min;
+        registry.pending_authority = None;
+        registry.previous_authority = Some(old.authority);
+        registry.version = CURRENT_VERSION;
+        registry.bump = ctx.bumps.registry;
+
+        msg!("Registry authority transfer accepted: old={} new={}",
+            old.authority, registry.authority);
+        Ok(())
+    }
+
+    /// Re-create a report of the previous registry authority under the new
+    /// authority's seeds and close the old report. Signed by the new authority.
+    /// Its ReportHistory and ReportFlags, when passed, move with it, and so does
+    /// its TokenReportIndex entry. Endorsements are keyed to the old report's
+    /// address, so the moved report starts with no votes.
+    pub fn migrate_report_authority(ctx: Context<MigrateReportAuthority>) -> Result<()> {
+        check_version(ctx.accounts.registry.version)?;
+        check_version(ctx.accounts.old_report.version)?;
+        let old = &ctx.accounts.old_report;
+        let report = &mut ctx.accounts.safety_report;
+        report.authority = ctx.accounts.authority.key();
+        report.token_mint = old.token_mint;
+        report.risk_score = old.risk_score;
+        report.risk_level = old.risk_level;
+        report.flags = old.flags;
+        report.flags_count = old.flags_count;
+        report.category = old.category;
+        report.protocol_name = old.protocol_name.clone();
+        report.report_uri = old.report_uri.clone();
+        report.timestamp = old.timestamp;
+        report.created_at = old.created_at;
+        report.min_tier = old.min_tier;
+        report.resolved = old.resolved;
+        report.active = old.active;
+        report.finalized = old.finalized;
+        report.report_slot = old.report_slot;
+        report.cosigner = old.cosigner;
+        report.version = CURRENT_VERSION;
+        report.bump = ctx.bumps.safety_report;
+        // Endorsement PDAs are seeded by the report address, so the old votes
+        // can't be carried over without letting each endorser vote again
+        report.endorsements = 0;
+        report.disputes = 0;
+        report.refresh_confidence();
+        let report_key = report.key();
+
+        match (ctx.accounts.old_history.as_ref(), ctx.accounts.report_history.as_mut()) {
+            (Some(old_history), Some(history)) => {
+                history.report = report_key;
+                history.total_versions = old_history.total_versions;
+                history.entries = old_history.entries.clone();
+                history.bump = ctx.bumps.report_history;
+            }
+            (None, None) => {}
+            _ => return err!(ErrorCode::MigrationAccountsMismatch),
+        }
+        match (ctx.accounts.old_flags.as_ref(), ctx.accounts.report_flags.as_mut()) {
+            (Some(old_flags), Some(flags)) => {
+                flags.report = report_key;
+                flags.reasons = old_flags.reasons.clone();
+                flags.bump = ctx.bumps.report_flags;
+            }
+            (None, None) => {}
+            _ => return err!(ErrorCode::MigrationAccountsMismatch),
+        }
+
+        let token_index = &mut ctx.accounts.token_index;
+        token_index.remove(&old.authority, report.report_slot);
+        token_index.insert(report.authority, report.report_slot);
+
+        let old_reporter = &mut ctx.accounts.old_reporter;
+        old_reporter.active_reports = old_reporter.active_reports.checked_sub(1).ok_or(ErrorCode::ArithmeticOverflow)?;
+        let reporter = &mut ctx.accounts.reporter;
+        reporter.init_if_new(report.authority, ctx.bumps.reporter);
+        reporter.active_reports = reporter.active_reports.checked_add(1).ok_or(ErrorCode::ArithmeticOverflow)?;
+
+        msg!("Report migrated: token_mint={} old_authority={} new_authority={}",
+            report.token_mint, old.authority, report.authority);
+        Ok(())
+    }
+
+    /// Rewrite a report stored by an older SafetyReport layout in the current
+    /// one. The old fields are decoded explicitly (see decode_old_report), the
+    /// account grows to the current size with the authority paying the rent
+    /// difference, and fields the old layout lacked get a new report's defaults.
+    pub fn migrate_report(ctx: Context<MigrateReport>) -> Result<()> {
+        let info = ctx.accounts.safety_report.to_account_info();
+        let report = decode_old_report(&info.try_borrow_data()?)?;
+        require_keys_eq!(report.authority, ctx.accounts.authority.key(), ErrorCode::Unauthorized);
+        let pda = Pubkey::create_program_address(
+            &[b"safety_report", report.token_mint.as_ref(), report.authority.as_ref(), report.slot_seed(), &[report.bump]],
+            ctx.program_id,
+        ).map_err(|_| error!(ErrorCode::InvalidReportAccount))?;
+        require_keys_eq!(info.key(), pda, ErrorCode::InvalidReportAccount);
+
+        let space = 8 + SafetyReport::INIT_SPACE;
+        let old_size = rewrite_migrated(&info, &ctx.accounts.authority.to_account_info(), space, &report)?;
+
+        msg!("Report migrated: token_mint={} authority={} size={}->{}",
+            report.token_mint, report.authority, old_size, space);
+        Ok(())
+    }
+
+    /// Rewrite a registry stored by an older Registry layout in the current one,
+    /// like migrate_report. Fields the old layout lacked get a new registry's
+    /// defaults, including the get_verdict thresholds.
+    pub fn migrate_registry(ctx: Context<MigrateRegistry>) -> Result<()> {
+        let info = ctx.accounts.registry.to_account_info();
+        let registry = decode_old_registry(&info.try_borrow_data()?)?;
+        require_keys_eq!(registry.authority, ctx.accounts.authority.key(), ErrorCode::Unauthorized);
+
+        let space = 8 + Registry::INIT_SPACE;
+        let old_size = rewrite_migrated(&info, &ctx.accounts.authority.to_account_info(), space, &registry)?;
+
+        msg!("Registry migrated: authority={} size={}->{}", registry.authority, old_size, space);
+        Ok(())
+    }
+
+    /// Admin: Rewrite the subscription config stored by an older
+    /// SubscriptionConfig layout in the current one, like migrate_report. The
+    /// basic/pro/alpha fields of layouts before version 3 become the first three
+    /// tiers; a v0.2.0 config, which had no lifetime price, gets `u64::MAX` until
+    /// update_pricing sets one. Other missing fields get initialization defaults.
+    pub fn migrate_subscription_config(ctx: Context<MigrateSubscriptionConfig>) -> Result<()> {
+        let info = ctx.accounts.subscription_config.to_account_info();
+        let (_, treasury_bump) = Pubkey::find_program_address(&[b"treasury"], ctx.program_id);
+        let mut config = decode_old_config(&info.try_borrow_data()?, treasury_bump)?;
+        require_keys_eq!(config.admin, ctx.accounts.admin.key(), ErrorCode::Unauthorized);
+        // A split set before `revenue_split_set` existed still has to be passed
+        config.revenue_split_set |= ctx.accounts.revenue_split.lamports() > 0;
+        config.record_admin_action()?;
+
+        let space = 8 + SubscriptionConfig::INIT_SPACE;
+        let old_size = rewrite_migrated(&info, &ctx.accounts.admin.to_account_info(), space, &config)?;
+
+        msg!("Subscription config migrated: tier_count={} size={}->{}", config.tier_count, old_size, space);
+        Ok(())
+    }
+
+    /// Rewrite a subscription stored by an older Subscription layout in the
+    /// current one, like migrate_report. A subscription from before tenure
+    /// tracking counts its tenure from `created_at`.
+    pub fn migrate_subscription(ctx: Context<MigrateSubscription>) -> Result<()> {
+        let info = ctx.accounts.subscription.to_account_info();
+        let subscription = decode_old_subscription(&info.try_borrow_data()?)?;
+        require_keys_eq!(subscription.user, ctx.accounts.user.key(), ErrorCode::Unauthorized);
+
+        let space = 8 + Subscription::INIT_SPACE;
+        let old_size = rewrite_migrated(&info, &ctx.accounts.user.to_account_info(), space, &subscription)?;
+
+        msg!("Subscription migrated: user={} tier={} size={}->{}",
+            subscription.user, subscription.tier, old_size, space);
+        Ok(())
+    }
+
+    /// Create the FlagIndex for a flag bit. Anyone may pay for it.
+    pub fn initialize_flag_index(ctx: Context<InitializeFlagIndex>, flag_bit: u8) -> Result<()> {
+        require!(flag_bit < 32 && VALID_FLAGS_MASK & (1 << flag_bit) != 0, ErrorCode::InvalidFlags);
+
+        let index = &mut ctx.accounts.flag_index;
+        index.flag_bit = flag_bit;
+        index.token_mints = Vec::new();
+        index.bump = ctx.bumps.flag_index;
+
+        msg!("Flag index initialized: bit={}", flag_bit);
+        Ok(())
+    }
+
+    /// Admin: Set the reporter reputation a registry requires for new reports.
+    pub fn set_min_reputation(ctx: Context<AdminRegistry>, min_reputation: u16) -> Result<()> {
+        check_version(ctx.accounts.registry.version)?;
+        ctx.accounts.subscription_config.record_admin_action()?;
+        require!(min_reputation <= MAX_REPUTATION, ErrorCode::ReputationTooHigh);
+
+        let registry = &mut ctx.accounts.registry;
+        registry.min_reputation = min_reputation;
+
+        msg!("Min reputation set: registry={} min_reputation={}", registry.authority, min_reputation);
+        Ok(())
+    }
+
+    /// Admin: Set how old (seconds since last update) a registry's reports may
+    /// be before check_report_fresh rejects them; 0 disables the check.
+    pub fn set_max_report_age(ctx: Context<AdminRegistry>, max_report_age: i64) -> Result<()> {
+        check_version(ctx.accounts.registry.version)?;
+        ctx.accounts.subscription_config.record_admin_action()?;
+        require!(max_report_age >= 0, ErrorCode::InvalidMaxReportAge);
+
+        let registry = &mut ctx.accounts.registry;
+        registry.max_report_age = max_report_age;
+
+        msg!("Max report age set: registry={} max_report_age={}s", registry.authority, max_report_age);
+        Ok(())
+    }
+
+    /// Admin: Set the minimum seconds between updates of a registry's reports (0 = no limit).
+    pub fn set_min_update_interval(ctx: Context<AdminRegistry>, min_update_interval: i64) -> Result<()> {
+        check_version(ctx.accounts.registry.version)?;
+        ctx.accounts.subscription_config.record_admin_action()?;
+        require!(min_update_interval >= 0, ErrorCode::InvalidUpdateInterval);
+
+        let registry = &mut ctx.accounts.registry;
+        registry.min_update_interval = min_update_interval;
+
+        msg!("Min update interval set: registry={} min_update_interval={}s",
+            registry.authority, min_update_interval);
+        Ok(())
+    }
+
+    /// Admin: Reject reports with `flag_penalty_threshold` or more flags that
+    /// score above `max_score_with_flags`. A threshold of 0 disables the check.
+    pub fn set_risk_consistency(
+        ctx: Context<AdminRegistry>,
+        flag_penalty_threshold: u8,
+        max_score_with_flags: u8,
+    ) -> Result<()> {
+        check_version(ctx.accounts.registry.version)?;
+        ctx.accounts.subscription_config.record_admin_action()?;
+        require!(
+            flag_penalty_threshold as u32 <= VALID_FLAGS_MASK.count_ones(),
+            ErrorCode::InvalidFlagThreshold
+        );
+        require!(max_score_with_flags <= 100, ErrorCode::InvalidRiskScore);
+
+        let registry = &mut ctx.accounts.registry;
+        registry.flag_penalty_threshold = flag_penalty_threshold;
+        registry.max_score_with_flags = max_score_with_flags;
+
+        msg!("Risk consistency set: registry={} flag_penalty_threshold={} max_score_with_flags={}",
+            registry.authority, flag_penalty_threshold, max_score_with_flags);
+        Ok(())
+    }
+
+    /// Admin: Set the minimum score and level (High=0, Medium=1, Low=2) at which
+    /// get_verdict calls a registry's report safe.
+    pub fn set_safe_thresholds(
+        ctx: Context<AdminRegistry>,
+        safe_score_min: u8,
+        safe_level_min: u8,
+    ) -> Result<()> {
+        check_version(ctx.accounts.registry.version)?;
+        ctx.accounts.subscription_config.record_admin_action()?;
+        require!(safe_score_min <= MAX_RISK_SCORE, ErrorCode::InvalidRiskScore);
+        require!(safe_level_min <= RiskLevel::Low as u8, ErrorCode::InvalidRiskLevel);
+
+        let registry = &mut ctx.accounts.registry;
+        registry.safe_score_min = safe_score_min;
+        registry.safe_level_min = safe_level_min;
+
+        msg!("Safe thresholds set: registry={} safe_score_min={} safe_level_min={}",
+            registry.authority, safe_score_min, safe_level_min);
+        Ok(())
+    }
+
+    /// Fails unless the report exists and was updated within its registry's
+    /// `max_report_age`. For integrators to assert freshness on-chain.
+    pub fn check_report_fresh(ctx: Context<CheckReportFresh>) -> Result<()> {
+        let report = &ctx.accounts.safety_report;
+        require!(report.active, ErrorCode::ReportInactive);
+        let max_age = ctx.accounts.registry.max_report_age;
+        let age = Clock::get()?.unix_timestamp.saturating_sub(report.timestamp);
+        require!(max_age == 0 || age <= max_age, ErrorCode::ReportStale);
+
+        msg!("Report fresh: report={} age={}s max={}s", report.key(), age, max_age);
+        Ok(())
+    }
+
+    /// Admin: Set a reporter's reputation, used to weight aggregated scores.
+    pub fn set_reputation(ctx: Context<SetReputation>, reputation: u16) -> Result<()> {
+        ctx.accounts.subscription_config.record_admin_action()?;
+        require!(reputation <= MAX_REPUTATION, ErrorCode::ReputationTooHigh);
+
+        let reporter = &mut ctx.accounts.reporter;
+        reporter.reputation = reputation;
+
+        msg!("Reputation set: reporter={} reputation={}", reporter.authority, reputation);
+        Ok(())
+    }
+
+    /// Log the reputation-weighted mean risk score for a token.
+    /// remaining_accounts: [SafetyReport, Reporter] pairs, one per reporting authority.
+    pub fn aggregate_report<'info>(
+        ctx: Context<'_, '_, 'info, 'info, AggregateReport<'info>>,
+    ) -> Result<()> {
+        let accounts = ctx.remaining_accounts;
+        require!(
+            !accounts.is_empty() && accounts.len() % 2 == 0,
+            ErrorCode::InvalidRemainingAccounts
+        );
+
+        let token_mint = ctx.accounts.token_mint.key();
+        let mut weighted_sum: u64 = 0;
+        let mut total_weight: u64 = 0;
+        let mut count = 0;
+        for pair in accounts.chunks(2) {
+            let report: Account<SafetyReport> = Account::try_from(&pair[0])?;
+            let reporter: Account<Reporter> = Account::try_from(&pair[1])?;
+            require_keys_eq!(report.token_mint, token_mint, ErrorCode::TokenMintMismatch);
+            require_keys_eq!(reporter.authority, report.authority, ErrorCode::ReporterMismatch);
+            if !report.active {
+                continue;
+            }
+
+            count += 1;
+            let weight = reporter.reputation as u64;
+            weighted_sum = weighted_sum.checked_add(report.risk_score as u64 * weight).ok_or(ErrorCode::ArithmeticOverflow)?;
+            total_weight = total_weight.checked_add(weight).ok_or(ErrorCode::ArithmeticOverflow)?;
+        }
+        require!(total_weight > 0, ErrorCode::ZeroTotalWeight);
+
+        let weighted_score = weighted_sum / total_weight;
+        msg!("Aggregate risk: mint={} reports={} total_weight={} weighted_score={}",
+            token_mint, count, total_weight, weighted_score);
+        Ok(())
+    }
+
+    /// Hide (`active = false`) or restore one of the signer's reports. Inactive
+    /// reports are skipped by aggregate_report and rejected by the read instructions,
+    /// and are left out of GlobalStats and the mint's TokenConsensus until restored.
+    pub fn set_report_active(ctx: Context<SetReportActive>, active: bool) -> Result<()> {
+        check_version(ctx.accounts.safety_report.version)?;
+        let report = &mut ctx.accounts.safety_report;
+        let was_active = report.active;
+        let was_in_consensus = report.in_consensus();
+        report.active = active;
+
+        let stats = &mut ctx.accounts.global_stats;
+        match (was_active, active) {
+            (false, true) => stats.add(report.risk_score, report.risk_level)?,
+            (true, false) => stats.remove(report.risk_score, report.risk_level)?,
+            _ => {}
+        }
+        let consensus = &mut ctx.accounts.consensus;
+        match (was_in_consensus, report.in_consensus()) {
+            (false, true) => consensus.add(report.risk_score, report.risk_level)?,
+            (true, false) => consensus.remove(report.risk_score, report.risk_level)?,
+            _ => {}
+        }
+
+        msg!("Report active set: report={} active={}", report.key(), active);
+        Ok(())
+    }
+
+    /// Permanently freeze one of the signer's reports for compliance. A finalized
+    /// report can't be updated, hidden, re-flagged, dispute-resolved or closed.
+    pub fn finalize_report(ctx: Context<FinalizeReport>) -> Result<()> {
+        check_version(ctx.accounts.safety_report.version)?;
+        let now = Clock::get()?.unix_timestamp;
+        let report = &mut ctx.accounts.safety_report;
+        report.finalized = true;
+
+        emit!(ReportFinalized {
+            report: report.key(),
+            token_mint: report.token_mint,
+            authority: report.authority,
+            risk_score: report.risk_score,
+            risk_level: report.risk_level,
+            flags: report.flags,
+            finalized_at: now,
+        });
+
+        msg!("Report finalized: report={} token_mint={}", report.key(), report.token_mint);
+        Ok(())
+    }
+
+    /// Replace the human-readable flag reasons attached to a report.
+    pub fn set_report_flags(ctx: Context<SetReportFlags>, reasons: Vec<String>) -> Result<()> {
+        check_version(ctx.accounts.safety_report.version)?;
+        require!(reasons.len() <= MAX_FLAG_REASONS, ErrorCode::TooManyFlagReasons);
+        require!(
+            reasons.iter().all(|r| r.len() <= MAX_FLAG_REASON_LEN),
+            ErrorCode::FlagReasonTooLong
+        );
+
+        let report_flags = &mut ctx.accounts.report_flags;
+        report_flags.report = ctx.accounts.safety_report.key();
+        report_flags.reasons = reasons;
+        report_flags.bump = ctx.bumps.report_flags;
+
+        msg!("Report flags set: report={} reasons={}",
+            report_flags.report, report_flags.reasons.len());
+        Ok(())
+    }
+
+    /// Log a report's score series (oldest history entry first, current score
+    /// last) and the change between the first and last score. Read-only.
+    pub fn get_risk_trend(ctx: Context<GetRiskTrend>) -> Result<()> {
+        let report = &ctx.accounts.safety_report;
+        require!(report.active, ErrorCode::ReportInactive);
+        let scores: Vec<u8> = ctx.accounts.report_history
+            .ordered()
+            .map(|entry| entry.risk_score)
+            .chain(std::iter::once(report.risk_score))
+            .collect();
+
+        let first = scores[0] as i16;
+        let delta = report.risk_score as i16 - first;
+        let trend = match delta {
+            d if d > 0 => "improving",
+            d if d < 0 => "worsening",
+            _ => "stable",
+        };
+
+        msg!("Risk trend: report={} scores={:?} delta={} trend={}",
+            report.key(), scores, delta, trend);
+
+        emit!(RiskTrend {
+            report: report.key(),
+            token_mint: report.token_mint,
+            scores,
+            delta,
+            improving: delta > 0,
+        });
+        Ok(())
+    }
+
+    /// Agree with (`agree = true`) or dispute another authority's report.
+    /// The endorser must own a registry; each endorser votes once per report.
+    pub fn endorse_report(ctx: Context<EndorseReport>, agree: bool) -> Result<()> {
+        check_version(ctx.accounts.safety_report.version)?;
+        require!(ctx.accounts.safety_report.active, ErrorCode::ReportInactive);
+        require!(!ctx.accounts.safety_report.resolved, ErrorCode::ReportResolved);
+        let report = &mut ctx.accounts.safety_report;
+        let endorser = ctx.accounts.endorser.key();
+        require_keys_neq!(endorser, report.authority, ErrorCode::SelfEndorsement);
+
+        if agree {
+            report.endorsements = report.endorsements.checked_add(1).ok_or(ErrorCode::ArithmeticOverflow)?;
+        } else {
+            report.disputes = report.disputes.checked_add(1).ok_or(ErrorCode::ArithmeticOverflow)?;
+        }
+        report.refresh_confidence();
+
+        let endorsement = &mut ctx.accounts.endorsement;
+        endorsement.report = report.key();
+        endorsement.endorser = endorser;
+        endorsement.agree = agree;
+        endorsement.timestamp = Clock::get()?.unix_timestamp;
+        endorsement.bump = ctx.bumps.endorsement;
+
+        msg!("Report endorsed: report={} endorser={} agree={} endorsements={} disputes={} confidence={}",
+            endorsement.report, endorser, agree, report.endorsements, report.disputes, report.confidence);
+        Ok(())
+    }
+
+    /// Admin: Settle a disputed report by force-setting its score, level and
+    /// flags. The report is marked resolved and takes no further endorsements.
+    /// remaining_accounts: the FlagIndex for each bit that changes, in ascending bit order.
+    pub fn resolve_dispute<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ResolveDispute<'info>>,
+        risk_score: u8,
+        risk_level: RiskLevel,
+        flags: u32,
+    ) -> Result<()> {
+        check_version(ctx.accounts.safety_report.version)?;
+        ctx.accounts.subscription_config.record_admin_action()?;
+        require!(risk_score <= 100, ErrorCode::InvalidRiskScore);
+        require!(flags & !VALID_FLAGS_MASK == 0, ErrorCode::InvalidFlags);
+
+        let report = &mut ctx.accounts.safety_report;
+        require!(report.disputes > 0, ErrorCode::NoOutstandingDisputes);
+        require!(!report.resolved, ErrorCode::ReportResolved);
+        update_flag_indexes(ctx.remaining_accounts, report.token_mint, report.flags, flags)?;
+        if report.active {
+            let stats = &mut ctx.accounts.global_stats;
+            stats.remove(report.risk_score, report.risk_level)?;
+            stats.add(risk_score, risk_level)?;
+        }
+        if report.in_consensus() {
+            let consensus = &mut ctx.accounts.consensus;
+            consensus.remove(report.risk_score, report.risk_level)?;
+            consensus.add(risk_score, risk_level)?;
+        }
+
+        report.risk_score = risk_score;
+        report.risk_level = risk_level;
+        report.flags = flags;
+        report.flags_count = flags.count_ones() as u8;
+        report.resolved = true;
+        report.timestamp = Clock::get()?.unix_timestamp;
+
+        emit!(DisputeResolved {
+            report: report.key(),
+            token_mint: report.token_mint,
+            risk_score,
+            risk_level,
+            flags,
+            endorsements: report.endorsements,
+            disputes: report.disputes,
+        });
+
+        msg!("Dispute resolved: report={} score={} level={} flags={}",
+            report.key(), risk_score, risk_level as u8, flags);
+        Ok(())
+    }
+
+    /// Return a report's main fields (Anchor writes them with `set_return_data`).
+    pub fn get_report(ctx: Context<GetReport>) -> Result<ReportView> {
+        let report = &ctx.accounts.safety_report;
+        require!(report.active, ErrorCode::ReportInactive);
+        Ok(ReportView {
+            token_mint: report.token_mint,
+            risk_score: report.risk_score,
+            risk_level: report.risk_level,
+            flags: report.flags,
+            category: report.category,
+            timestamp: report.timestamp,
+            protocol_name: report.protocol_name.clone(),
+        })
+    }
+
+    /// Return whether a report is safe under its registry's thresholds:
+    /// `risk_score >= safe_score_min && risk_level >= safe_level_min`
+    /// (Anchor writes it with `set_return_data`).
+    pub fn get_verdict(ctx: Context<GetVerdict>) -> Result<VerdictView> {
+        let report = &ctx.accounts.safety_report;
+        require!(report.active, ErrorCode::ReportInactive);
+        let registry = &ctx.accounts.registry;
+        let safe = report.is_safe(registry.safe_score_min)
+            && report.risk_level as u8 >= registry.safe_level_min;
+
+        msg!("Verdict: mint={} score={} level={} safe={}",
+            report.token_mint, report.risk_score, report.risk_level as u8, safe);
+        Ok(VerdictView { safe })
+    }
+
+    /// Return registry-wide report totals (Anchor writes them with `set_return_data`).
+    pub fn get_global_stats(ctx: Context<GetGlobalStats>) -> Result<GlobalStatsView> {
+        let stats = &ctx.accounts.global_stats;
+        Ok(GlobalStatsView {
+            total_reports: stats.total_reports,
+            average_score: stats.average_score(),
+            high_reports: stats.high_reports,
+            medium_reports: stats.medium_reports,
+            low_reports: stats.low_reports,
+        })
+    }
+
+    /// Return the risk_score convention (Anchor writes it with `set_return_data`)
+    /// and emit it as a ScoreConvention event, so clients can confirm the
+    /// score direction programmatically.
+    pub fn get_conventions(_ctx: Context<GetConventions>) -> Result<ConventionsView> {
+        emit!(ScoreConvention {
+            score_convention: SCORE_CONVENTION,
+            max_score: MAX_RISK_SCORE,
+        });
+        Ok(ConventionsView {
+            score_convention: SCORE_CONVENTION,
+            max_score: MAX_RISK_SCORE,
+        })
+    }
+
+    /// Return the authorities with a report on a token and the report slots
+    /// each holds, oldest first (Anchor writes it with `set_return_data`), up
+    /// to MAX_TOKEN_INDEX_LEN.
+    pub fn get_token_reports(ctx: Context<GetTokenReports>) -> Result<Vec<TokenReporterView>> {
+        let index = &ctx.accounts.token_index;
+        Ok(index
+            .authorities
+            .iter()
+            .zip(&index.slots)
+            .map(|(&authority, &slots)| TokenReporterView { authority, slots })
+            .collect())
+    }
+
+    /// Return the cross-reporter consensus for a token (Anchor writes it with `set_return_data`).
+    pub fn get_consensus(ctx: Context<GetConsensus>) -> Result<ConsensusView> {
+        let consensus = &ctx.accounts.consensus;
+        Ok(ConsensusView {
+            token_mint: consensus.token_mint,
+            report_count: consensus.report_count,
+            average_score: consensus.average_score(),
+            min_level: consensus.min_level,
+        })
+    }
+
+    /// Return a report's contents to a subscriber whose active tier is at least
+    /// the report's `min_tier` (Anchor writes it with `set_return_data`).
+    pub fn read_gated_report(ctx: Context<ReadGatedReport>) -> Result<SafetyReport> {
+        let now = Clock::get()?.unix_timestamp;
+        ctx.accounts.subscription.apply_pending_tier(now);
+        let subscription = &ctx.accounts.subscription;
+        let report = &ctx.accounts.safety_report;
+        require!(report.active, ErrorCode::ReportInactive);
+
+        let active_until = subscription
+            .expires_at
+            .saturating_add(ctx.accounts.subscription_config.grace_period);
+        require!(
+            active_until > now && subscription.tier >= report.min_tier,
+            ErrorCode::InsufficientSubscription
+        );
+
+        msg!("Gated report read: report={} user={} tier={} min_tier={}",
+            report.key(), subscription.user, subscription.tier, report.min_tier);
+        Ok(report.clone().into_inner())
+    }
+
+    // ========================================================================
+    // Subscription Management (SOL and SPL token payments)
+    // ========================================================================
+
+    /// Initialize the subscription config (admin only, once).
+    /// `tier_prices[i]` and `tier_durations[i]` configure tier `i + 1`.
+    pub fn initialize_subscription_config(
+        ctx: Context<InitializeSubscriptionConfig>,
+        tier_prices: Vec<u64>,      // Lamports per tier
+        lifetime_price_lamports: u64,
+        tier_durations: Vec<i64>,   // Cycle length per tier, in seconds
+        grace_period: i64,          // Seconds of access after expiry; 0 = none
+    ) -> Result<()> {
+        validate_pricing(&tier_prices)?;
+        validate_durations(&tier_durations)?;
+        require!(tier_prices.len() == tier_durations.len(), ErrorCode::InvalidTierCount);
+        require!(grace_period >= 0, ErrorCode::InvalidGracePeriod);
+        require_keys_neq!(ctx.accounts.treasury.key(), Pubkey::default(), ErrorCode::InvalidTreasury);
+
+        let config = &mut ctx.accounts.subscription_config;
+        config.admin = ctx.accounts.admin.key();
+        config.treasury = ctx.accounts.treasury.key();
+        config.token_treasury = ctx.accounts.treasury.key();
+        config.tier_count = tier_prices.len() as u8;
+        config.tier_subscribers = vec![0; tier_prices.len()];
+        config.tier_revenue = vec![0; tier_prices.len()];
+        config.tier_prices = tier_prices;
+        config.lifetime_price = lifetime_price_lamports;
+        config.promo_prices = Vec::new();
+        config.promo_ends_at = 0;
+        config.tier_durations = tier_durations;
+        config.grace_period = grace_period;
+        config.total_subscribers = 0;
+        config.max_subscribers = 0;
+        config.report_quotas = Vec::new();
+        config.report_quota_period = DEFAULT_REPORT_QUOTA_PERIOD;
+        config.total_revenue = 0;
+        config.treasury_bump = ctx.bumps.treasury;
+        config.pending_admin = None;
+        config.referral_bps = 0;
+        config.trial_duration = DEFAULT_TRIAL_DURATION;
+        config.refund_window = 0;
+        config.refund_mode = REFUND_MODE_PRORATED;
+        config.whitelist_root = [0; 32];
+        config.annual_discount_bps = 0;
+        config.late_penalty_bps = 0;
+        config.subscribe_cooldown = 0;
+        config.revenue_split_set = false;
+        config.last_admin_action = Clock::get()?.unix_timestamp;
+        config.inactivity_timeout = 0;
+        config.paused = false;
+        config.version = CURRENT_VERSION;
+        config.bump = ctx.bumps.subscription_config;
+
+        msg!("Subscription config initialized. Treasury: {}", ctx.accounts.treasury.key());
+        Ok(())
+    }
+
+    /// Subscribe with SOL payment.
+    /// Tier: 1 = Basic, 2 = Pro, 3 = Alpha
+    /// If `referrer` is set, `referral_bps` of the price goes to the referrer's wallet
+    /// and the remainder to the treasury, or across the RevenueSplit recipients
+    /// (remaining_accounts, in entry order) when `revenue_split` is passed.
+    /// A `coupon` is only accepted with its plain `coupon_code`, which seeds its PDA.
+    pub fn subscribe<'info>(
+        ctx: Context<'_, '_, 'info, 'info, Subscribe<'info>>,
+        tier: u8,
+        referrer: Option<Pubkey>,
+        coupon_code: Option<String>,
+        max_price: u64, // Fails if the price exceeds this; u64::MAX to opt out
+    ) -> Result<()> {
+        // Validate everything before any state changes or funds move
+        check_version(ctx.accounts.subscription_config.version)?;
+        require!(!ctx.accounts.subscription_config.paused, ErrorCode::SubscriptionsPaused);
+        ctx.accounts.subscription_config.check_tier(tier)?;
+        require!(ctx.accounts.subscription.is_new_or_trial(), ErrorCode::SubscriptionAlreadyExists);
+        require_keys_neq!(ctx.accounts.treasury.key(), ctx.accounts.user.key(), ErrorCode::TreasuryIsUser);
+
+        let now = Clock::get()?.unix_timestamp;
+        ctx.accounts.subscriber_meta.record_action(
+            ctx.accounts.user.key(),
+            ctx.bumps.subscriber_meta,
+            ctx.accounts.subscription_config.subscribe_cooldown,
+            now,
+        )?;
+        let config = &ctx.accounts.subscription_config;
+        let list_price = config.current_price(tier, now)?;
+        let new_expiry = now.checked_add(config.tier_duration(tier)?).ok_or(ErrorCode::ArithmeticOverflow)?;
+
+        // Apply the coupon's discount, if any
+        let mut discount = 0;
+        if let Some(coupon) = ctx.accounts.coupon.as_ref() {
+            require!(coupon_code.is_some(), ErrorCode::InvalidCouponCode);
+            require!(now < coupon.expires_at, ErrorCode::CouponExpired);
+            require!(coupon.uses < coupon.max_uses, ErrorCode::CouponExhausted);
+            discount = bps_of(list_price, coupon.discount_bps);
+        }
+        let price = list_price.checked_sub(discount).ok_or(ErrorCode::ArithmeticOverflow)?;
+        require!(price <= max_price, ErrorCode::PriceExceedsMax);
+
+        // Work out the referrer's share, if any
+        let mut referral_cut = 0;
+        if let Some(referrer) = referrer {
+            require_keys_neq!(referrer, ctx.accounts.user.key(), ErrorCode::SelfReferral);
+            let referrer_wallet = ctx.accounts.referrer_wallet.as_ref()
+                .ok_or(ErrorCode::InvalidReferral)?;
+            require_keys_eq!(referrer_wallet.key(), referrer, ErrorCode::InvalidReferral);
+            let referral = ctx.accounts.referral.as_ref()
+                .ok_or(ErrorCode::InvalidReferral)?;
+            require_keys_eq!(referral.referrer, referrer, ErrorCode::InvalidReferral);
+            referral_cut = bps_of(price, config.referral_bps);
+        }
+        let treasury_amount = price.checked_sub(referral_cut).ok_or(ErrorCode::ArithmeticOverflow)?;
+
+        // Create subscription
+        let subscription = &mut ctx.accounts.subscription;
+        subscription.user = ctx.accounts.user.key();
+        subscription.tier = tier;
+        subscription.accrue_active_time(now)?;
+        subscription.expires_at = new_expiry;
+        subscription.created_at = now;
+        subscription.total_paid = price;
+        subscription.auto_renew = false;
+        subscription.is_trial = false;
+        subscription.disabled = false;
+        subscription.version = CURRENT_VERSION;
+        subscription.bump = ctx.bumps.subscription;
+
+        // Update coupon, referral and config stats (add_subscriber enforces the cap)
+        if let Some(coupon) = ctx.accounts.coupon.as_mut() {
+            coupon.uses = coupon.uses.checked_add(1).ok_or(ErrorCode::ArithmeticOverflow)?;
+        }
+        if referrer.is_some() {
+            let referral = ctx.accounts.referral.as_mut().ok_or(ErrorCode::InvalidReferral)?;
+            referral.total_referred = referral.total_referred.checked_add(1).ok_or(ErrorCode::ArithmeticOverflow)?;
+            referral.total_earned = referral.total_earned.checked_add(referral_cut).ok_or(ErrorCode::ArithmeticOverflow)?;
+        }
+        let config = &mut ctx.accounts.subscription_config;
+        config.add_subscriber(tier)?;
+        config.add_revenue(tier, treasury_amount)?;
+
+        ctx.accounts.subscription_history.record(
+            ctx.accounts.user.key(),
+            ctx.bumps.subscription_history,
+            SubscriptionHistoryEntry { action: SUB_ACTION_SUBSCRIBE, tier, price, timestamp: now },
+        );
+
+        // Move funds last, so any failure above aborts before a transfer
+        if referrer.is_some() {
+            let referrer_wallet = ctx.accounts.referrer_wallet.as_ref().ok_or(ErrorCode::InvalidReferral)?;
+            transfer_sol_verified(
+                &ctx.accounts.user.to_account_info(),
+                &referrer_wallet.to_account_info(),
+                referral_cut,
+            )?;
+        }
+        pay_revenue(
+            &ctx.accounts.user.to_account_info(),
+            &ctx.accounts.treasury.to_account_info(),
+            ctx.accounts.subscription_config.revenue_split_set,
+            ctx.accounts.revenue_split.as_deref(),
+            ctx.remaining_accounts,
+            treasury_amount,
+        )?;
+        emit!(TreasuryInflow {
+            source: ctx.accounts.user.key(),
+            amount: treasury_amount,
+            reason: INFLOW_SUBSCRIBE,
+            timestamp: now,
+        });
+
+        let subscription = &ctx.accounts.subscription;
+        emit!(SubscriptionPurchased {
+            subscription: subscription.key(),
+            user: subscription.user,
+            tier,
+            price,
+            expires_at: new_expiry,
+            created_at: now,
+        });
+
+        msg!("Subscription created: user={} tier={} expires={} referral_cut={} discount={}", 
+            ctx.accounts.user.key(), tier, new_expiry, referral_cut, discount);
+        Ok(())
+    }
+
+    /// Return the signer's subscription history, oldest first (Anchor writes it
+    /// with `set_return_data`). Only the last SUBSCRIPTION_HISTORY_LEN entries are kept.
+    pub fn get_subscription_history(ctx: Context<GetSubscriptionHistory>) -> Result<Vec<SubscriptionHistoryEntry>> {
+        Ok(ctx.accounts.subscription_history.ordered().copied().collect())
+    }
+
+    /// Log a subscription's tenure: when it first became paid and its total
+    /// active seconds, counting the current period up to now. Read-only.
+    pub fn get_loyalty(ctx: Context<GetLoyalty>) -> Result<()> {
+        let subscription = &ctx.accounts.subscription;
+        let now = Clock::get()?.unix_timestamp;
+        let active_seconds = subscription.loyalty_seconds(now);
+
+        msg!("Loyalty: user={} first_subscribed_at={} active_seconds={} active_days={}",
+            subscription.user, subscription.first_subscribed_at, active_seconds, active_seconds / 86_400);
+        Ok(())
+    }
+
+    /// Register the signer as a referrer so they can earn a share of referred subscriptions.
+    pub fn register_referrer(ctx: Context<RegisterReferrer>) -> Result<()> {
+        let referral = &mut ctx.accounts.referral;
+        referral.referrer = ctx.accounts.referrer.key();
+        referral.total_referred = 0;
+        referral.total_earned = 0;
+        referral.bump = ctx.bumps.referral;
+
+        msg!("Referrer registered: {}", referral.referrer);
+        Ok(())
+    }
+
+    /// Subscribe with an SPL token payment (e.g. USDC).
+    /// The payment mint must have an `AcceptedMint` account, which sets its prices.
+    /// Tier: 1 to `tier_count`, and priced by the mint
+    pub fn subscribe_spl(ctx: Context<SubscribeSpl>, tier: u8) -> Result<()> {
+        check_version(ctx.accounts.subscription_config.version)?;
+        require!(!ctx.accounts.subscription_config.paused, ErrorCode::SubscriptionsPaused);
+        ctx.accounts.subscription_config.check_tier(tier)?;
+        require!(ctx.accounts.subscription.is_new_or_trial(), ErrorCode::SubscriptionAlreadyExists);
+
+        let now = Clock::get()?.unix_timestamp;
+        ctx.accounts.subscriber_meta.record_action(
+            ctx.accounts.user.key(),
+            ctx.bumps.subscriber_meta,
+            ctx.accounts.subscription_config.subscribe_cooldown,
+            now,
+        )?;
+        let config = &ctx.accounts.subscription_config;
+        let accepted_mint = &ctx.accounts.accepted_mint;
+        let price = accepted_mint.tier_price(tier)?;
+
+        // Transfer tokens from user to treasury token account
+        token::transfer(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.user_token_account.to_account_info(),
+                    to: ctx.accounts.treasury_token_account.to_account_info(),
+                    authority: ctx.accounts.user.to_account_info(),
+                },
+            ),
+            price,
+        )?;
+
+        // Create subscription (total_paid tracks lamports only)
+        let subscription = &mut ctx.accounts.subscription;
+        let new_expiry = now.checked_add(config.tier_duration(tier)?).ok_or(ErrorCode::ArithmeticOverflow)?;
+
+        subscription.user = ctx.accounts.user.key();
+        subscription.tier = tier;
+        subscription.accrue_active_time(now)?;
+        subscription.expires_at = new_expiry;
+        subscription.created_at = now;
+        subscription.total_paid = 0;
+        subscription.auto_renew = false;
+        subscription.is_trial = false;
+        subscription.version = CURRENT_VERSION;
+        subscription.bump = ctx.bumps.subscription;
+
+        // Update config and per-mint stats
+        let config = &mut ctx.accounts.subscription_config;
+        config.add_subscriber(tier)?;
+        let accepted_mint = &mut ctx.accounts.accepted_mint;
+        accepted_mint.total_revenue = accepted_mint.total_revenue.checked_add(price).ok_or(ErrorCode::ArithmeticOverflow)?;
+
+        msg!("Subscription created (SPL): user={} tier={} mint={} expires={}",
+            ctx.accounts.user.key(), tier, accepted_mint.mint, new_expiry);
+        Ok(())
+    }
+
+    /// Buy permanent access to the top tier (`tier_count`) for `config.lifetime_price`.
+    /// Replaces any existing non-lifetime subscription without refunding its
+    /// remaining time.
+    pub fn subscribe_lifetime(ctx: Context<SubscribeLifetime>) -> Result<()> {
+        check_version(ctx.accounts.subscription_config.version)?;
+        require!(!ctx.accounts.subscription_config.paused, ErrorCode::SubscriptionsPaused);
+        let subscription = &ctx.accounts.subscription;
+        require!(!subscription.lifetime, ErrorCode::LifetimeSubscription);
+        require_keys_neq!(ctx.accounts.treasury.key(), ctx.accounts.user.key(), ErrorCode::TreasuryIsUser);
+        let already_counted = !subscription.is_new_or_trial() && subscription.tier != 0;
+
+        let now = Clock::get()?.unix_timestamp;
+        ctx.accounts.subscriber_meta.record_action(
+            ctx.accounts.user.key(),
+            ctx.bumps.subscriber_meta,
+            ctx.accounts.subscription_config.subscribe_cooldown,
+            now,
+        )?;
+        let price = ctx.accounts.subscription_config.lifetime_price;
+        transfer_sol_verified(
+            &ctx.accounts.user.to_account_info(),
+            &ctx.accounts.treasury.to_account_info(),
+            price,
+        )?;
+
+        emit!(TreasuryInflow {
+            source: ctx.accounts.user.key(),
+            amount: price,
+            reason: INFLOW_LIFETIME,
+            timestamp: now,
+        });
+        let subscription = &mut ctx.accounts.subscription;
+        subscription.user = ctx.accounts.user.key();
+        subscription.tier = ctx.accounts.subscription_config.tier_count;
+        subscription.accrue_active_time(now)?;
+        subscription.expires_at = i64::MAX;
+        subscription.created_at = now;
+        subscription.total_paid = subscription.total_paid.checked_add(price).ok_or(ErrorCode::ArithmeticOverflow)?;
+        subscription.auto_renew = false;
+        subscription.is_trial = false;
+        subscription.pending_tier = 0;
+        subscription.pending_tier_at = 0;
+        subscription.lifetime = true;
+        subscription.version = CURRENT_VERSION;
+        subscription.bump = ctx.bumps.subscription;
+
+        let config = &mut ctx.accounts.subscription_config;
+        if !already_counted {
+            config.add_subscriber(subscription.tier)?;
+        }
+        config.add_revenue(subscription.tier, price)?;
+
+        emit!(SubscriptionPurchased {
+            subscription: subscription.key(),
+            user: subscription.user,
+            tier: subscription.tier,
+            price,
+            expires_at: subscription.expires_at,
+            created_at: now,
+        });
+
+        msg!("Lifetime subscription created: user={} price={}", subscription.user, price);
+        Ok(())
+    }
+
+    /// Buy ANNUAL_CYCLES cycles of `tier` up front at `annual_discount_bps` off
+    /// the list price. Like subscribe, only for wallets with no paid
+    /// subscription; a trial is converted.
+    pub fn subscribe_annual(
+        ctx: Context<SubscribeAnnual>,
+        tier: u8,
+        max_price: u64, // Fails if the price exceeds this; u64::MAX to opt out
+    ) -> Result<()> {
+        check_version(ctx.accounts.subscription_config.version)?;
+        require!(!ctx.accounts.subscription_config.paused, ErrorCode::SubscriptionsPaused);
+        ctx.accounts.subscription_config.check_tier(tier)?;
+        require!(ctx.accounts.subscription.is_new_or_trial(), ErrorCode::SubscriptionAlreadyExists);
+        require_keys_neq!(ctx.accounts.treasury.key(), ctx.accounts.user.key(), ErrorCode::TreasuryIsUser);
+
+        let now = Clock::get()?.unix_timestamp;
+        ctx.accounts.subscriber_meta.record_action(
+            ctx.accounts.user.key(),
+            ctx.bumps.subscriber_meta,
+            ctx.accounts.subscription_config.subscribe_cooldown,
+            now,
+        )?;
+        let config = &ctx.accounts.subscription_config;
+        let price = config.annual_price(tier)?;
+        require!(price <= max_price, ErrorCode::PriceExceedsMax);
+        let duration = config.tier_duration(tier)?
+            .checked_mul(ANNUAL_CYCLES as i64)
+            .ok_or(ErrorCode::ArithmeticOverflow)?;
+        let new_expiry = now.checked_add(duration).ok_or(ErrorCode::ArithmeticOverflow)?;
+
+        let subscription = &mut ctx.accounts.subscription;
+        subscription.user = ctx.accounts.user.key();
+        subscription.tier = tier;
+        subscription.accrue_active_time(now)?;
+        subscription.expires_at = new_expiry;
+        subscription.created_at = now;
+        subscription.total_paid = price;
+        subscription.auto_renew = false;
+        subscription.is_trial = false;
+        subscription.disabled = false;
+        subscription.version = CURRENT_VERSION;
+        subscription.bump = ctx.bumps.subscription;
+
+        let config = &mut ctx.accounts.subscription_config;
+        config.add_subscriber(tier)?;
+        config.add_revenue(tier, price)?;
+
+        ctx.accounts.subscription_history.record(
+            ctx.accounts.user.key(),
+            ctx.bumps.subscription_history,
+            SubscriptionHistoryEntry { action: SUB_ACTION_SUBSCRIBE, tier, price, timestamp: now },
+        );
+
+        transfer_sol_verified(
+            &ctx.accounts.user.to_account_info(),
+            &ctx.accounts.treasury.to_account_info(),
+            price,
+        )?;
+        emit!(TreasuryInflow {
+            source: ctx.accounts.user.key(),
+            amount: price,
+            reason: INFLOW_ANNUAL,
+            timestamp: now,
+        });
+
+        let subscription = &ctx.accounts.subscription;
+        emit!(SubscriptionPurchased {
+            subscription: subscription.key(),
+            user: subscription.user,
+            tier,
+            price,
+            expires_at: new_expiry,
+            created_at: now,
+        });
+
+        msg!("Annual subscription created: user={} tier={} expires={} price={}",
+            subscription.user, tier, new_expiry, price);
+        Ok(())
+    }
+
+    /// Mint a receipt NFT for the signer's active paid subscription: a
+    /// 0-decimal mint with supply 1, held in the user's associated token account
+    /// and frozen so it can't be transferred. The subscription PDA is its mint
+    /// and freeze authority. One receipt per subscription account.
+    /// A separate instruction from the purchase, so a failed mint never rolls
+    /// back a subscription; send it in its own transaction after subscribing.
+    pub fn mint_receipt(ctx: Context<MintReceipt>) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let subscription = &ctx.accounts.subscription;
+        check_version(subscription.version)?;
+        require!(subscription.receipt_mint == Pubkey::default(), ErrorCode::ReceiptAlreadyMinted);
+        require!(subscription.is_active_paid(now), ErrorCode::InsufficientSubscription);
+
+        let user = ctx.accounts.user.key();
+        let bump = [subscription.bump];
+        let seeds: &[&[u8]] = &[b"subscription", user.as_ref(), &bump];
+        let signer = &[seeds];
+        token::mint_to(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                MintTo {
+                    mint: ctx.accounts.receipt_mint.to_account_info(),
+                    to: ctx.accounts.receipt_token.to_account_info(),
+                    authority: ctx.accounts.subscription.to_account_info(),
+                },
+                signer,
+            ),
+            1,
+        )?;
+        token::freeze_account(CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            FreezeAccount {
+                account: ctx.accounts.receipt_token.to_account_info(),
+                mint: ctx.accounts.receipt_mint.to_account_info(),
+                authority: ctx.accounts.subscription.to_account_info(),
+            },
+            signer,
+        ))?;
+
+        let subscription = &mut ctx.accounts.subscription;
+        subscription.receipt_mint = ctx.accounts.receipt_mint.key();
+
+        msg!("Receipt minted: user={} mint={} tier={} expires={}",
+            user, subscription.receipt_mint, subscription.tier, subscription.expires_at);
+        Ok(())
+    }
+
+    /// Start a one-time free Pro trial lasting `config.trial_duration`.
+    /// Each wallet can trial once; a `TrialRecord` marks it as used.
+    /// Trials are not counted in `total_subscribers` until converted by
+    /// subscribe / renew_subscription.
+    pub fn start_trial(ctx: Context<StartTrial>) -> Result<()> {
+        check_version(ctx.accounts.subscription_config.version)?;
+        let config = &ctx.accounts.subscription_config;
+        let now = Clock::get()?.unix_timestamp;
+        let new_expiry = now.checked_add(config.trial_duration).ok_or(ErrorCode::ArithmeticOverflow)?;
+
+        let subscription = &mut ctx.accounts.subscription;
+        subscription.user = ctx.accounts.user.key();
+        subscription.tier = 2;
+        subscription.expires_at = new_expiry;
+        subscription.created_at = now;
+        subscription.total_paid = 0;
+        subscription.auto_renew = false;
+        subscription.is_trial = true;
+        subscription.version = CURRENT_VERSION;
+        subscription.bump = ctx.bumps.subscription;
+
+        let trial = &mut ctx.accounts.trial_record;
+        trial.user = ctx.accounts.user.key();
+        trial.started_at = now;
+        trial.bump = ctx.bumps.trial_record;
+
+        msg!("Trial started: user={} expires={}", trial.user, new_expiry);
+        Ok(())
+    }
+
+    /// Renew, upgrade or downgrade an existing subscription.
+    /// Upgrading an active subscription takes effect immediately and credits the
+    /// unused value of the current tier against the new price; the new cycle then
+    /// starts now instead of stacking on the old expiry, since the remaining time
+    /// was refunded as credit.
+    /// Downgrading an active subscription is scheduled instead: the user keeps the
+    /// current tier until `expires_at`, after which `pending_tier` applies for the
+    /// newly paid cycle. Any other renewal clears a scheduled downgrade.
+    /// When `revenue_split` is passed, the charge is split across its recipients
+    /// (remaining_accounts, in entry order) instead of going to the treasury.
+    pub fn renew_subscription<'info>(
+        ctx: Context<'_, '_, 'info, 'info, RenewSubscription<'info>>,
+        tier: u8,
+        max_price: u64, // Fails if the charge exceeds this; u64::MAX to opt out
+    ) -> Result<()> {
+        check_version(ctx.accounts.subscription_config.version)?;
+        check_version(ctx.accounts.subscription.version)?;
+        require!(!ctx.accounts.subscription_config.paused, ErrorCode::SubscriptionsPaused);
+        ctx.accounts.subscription_config.check_tier(tier)?;
+        require!(!ctx.accounts.subscription.lifetime, ErrorCode::LifetimeSubscription);
+        require_keys_neq!(ctx.accounts.treasury.key(), ctx.accounts.user.key(), ErrorCode::TreasuryIsUser);
+
+        let clock = Clock::get()?;
+        let now = clock.unix_timestamp;
+        ctx.accounts.subscription.apply_pending_tier(now);
+        let (mut charge, credit) = ctx.accounts.subscription
+            .renewal_charge(&ctx.accounts.subscription_config, tier, now)?;
+        // A paid subscription renewed after it lapsed pays the late surcharge
+        let subscription = &ctx.accounts.subscription;
+        let late_penalty = !subscription.is_trial && subscription.tier != 0 && subscription.expires_at < now;
+        if late_penalty {
+            charge = ctx.accounts.subscription_config.with_late_penalty(charge)?;
+        }
+        require!(charge <= max_price, ErrorCode::PriceExceedsMax);
+
+        // Transfer SOL from user to treasury
+        pay_revenue(
+            &ctx.accounts.user.to_account_info(),
+            &ctx.accounts.treasury.to_account_info(),
+            ctx.accounts.subscription_config.revenue_split_set,
+            ctx.accounts.revenue_split.as_deref(),
+            ctx.remaining_accounts,
+            charge,
+        )?;
+        emit!(TreasuryInflow {
+            source: ctx.accounts.user.key(),
+            amount: charge,
+            reason: INFLOW_RENEW,
+            timestamp: now,
+        });
+
+        // Update subscription
+        let config = &ctx.accounts.subscription_config;
+        let subscription = &mut ctx.accounts.subscription;
+        let old_tier = subscription.tier;
+        let was_counted = !subscription.is_trial && old_tier != 0;
+        let new_expiry = subscription.apply_renewal(config, tier, charge, now)?;
+
+        // Update config stats
+        let config = &mut ctx.accounts.subscription_config;
+        if !was_counted {
+            config.add_subscriber(tier)?;
+        }
+        config.add_revenue(tier, charge)?;
+
+        let action = match tier.cmp(&old_tier) {
+            std::cmp::Ordering::Greater if was_counted => SUB_ACTION_UPGRADE,
+            std::cmp::Ordering::Less if was_counted => SUB_ACTION_DOWNGRADE,
+            _ => SUB_ACTION_RENEW,
+        };
+        ctx.accounts.subscription_history.record(
+            subscription.user,
+            ctx.bumps.subscription_history,
+            SubscriptionHistoryEntry { action, tier, price: charge, timestamp: now },
+        );
+
+        emit!(SubscriptionRenewed {
+            subscription: subscription.key(),
+            user: subscription.user,
+            old_tier,
+            new_tier: tier,
+            price: charge,
+            expires_at: new_expiry,
+            late_penalty,
+        });
+
+        msg!("Subscription renewed: user={} tier={} expires={} charged={} credit={} late_penalty={} pending_tier={}", 
+            subscription.user, subscription.tier, new_expiry, charge, credit, late_penalty, subscription.pending_tier);
+        Ok(())
+    }
+
+    /// Extend an active paid subscription by `days` at its current tier, paying
+    /// the tier's price (promo price while a promo runs) pro-rated by time:
+    /// price * days * 86400 / tier duration, rounded down.
+    /// Payments follow `revenue_split` exactly as renew_subscription does.
+    pub fn topup_subscription<'info>(
+        ctx: Context<'_, '_, 'info, 'info, RenewSubscription<'info>>,
+        days: u32,
+        max_price: u64, // Fails if the charge exceeds this; u64::MAX to opt out
+    ) -> Result<()> {
+        check_version(ctx.accounts.subscription_config.version)?;
+        check_version(ctx.accounts.subscription.version)?;
+        require!(!ctx.accounts.subscription_config.paused, ErrorCode::SubscriptionsPaused);
+        require!(days >= 1 && days <= MAX_TOPUP_DAYS, ErrorCode::InvalidTopupDays);
+        require!(!ctx.accounts.subscription.lifetime, ErrorCode::LifetimeSubscription);
+        require_keys_neq!(ctx.accounts.treasury.key(), ctx.accounts.user.key(), ErrorCode::TreasuryIsUser);
+
+        let now = Clock::get()?.unix_timestamp;
+        ctx.accounts.subscription.apply_pending_tier(now);
+        let subscription = &ctx.accounts.subscription;
+        require!(subscription.is_active_paid(now), ErrorCode::InsufficientSubscription);
+
+        let config = &ctx.accounts.subscription_config;
+        let tier = subscription.tier;
+        let extension = (days as i64).checked_mul(86_400).ok_or(ErrorCode::ArithmeticOverflow)?;
+        let charge = (config.current_price(tier, now)? as u128)
+            .checked_mul(extension as u128)
+            .ok_or(ErrorCode::ArithmeticOverflow)?
+            / config.tier_duration(tier)? as u128;
+        let charge = u64::try_from(charge).map_err(|_| error!(ErrorCode::ArithmeticOverflow))?;
+        require!(charge <= max_price, ErrorCode::PriceExceedsMax);
+        let new_expiry = subscription.expires_at.checked_add(extension).ok_or(ErrorCode::ArithmeticOverflow)?;
+
+        pay_revenue(
+            &ctx.accounts.user.to_account_info(),
+            &ctx.accounts.treasury.to_account_info(),
+            ctx.accounts.subscription_config.revenue_split_set,
+            ctx.accounts.revenue_split.as_deref(),
+            ctx.remaining_accounts,
+            charge,
+        )?;
+        emit!(TreasuryInflow {
+            source: ctx.accounts.user.key(),
+            amount: charge,
+            reason: INFLOW_TOPUP,
+            timestamp: now,
+        });
+
+        let subscription = &mut ctx.accounts.subscription;
+        subscription.accrue_active_time(now)?;
+        subscription.expires_at = new_expiry;
+        subscription.total_paid = subscription.total_paid.checked_add(charge).ok_or(ErrorCode::ArithmeticOverflow)?;
+        if subscription.pending_tier != 0 {
+            // The bought days are at the current tier; a scheduled downgrade waits for them
+            subscription.pending_tier_at = new_expiry;
+        }
+        ctx.accounts.subscription_config.add_revenue(tier, charge)?;
+
+        ctx.accounts.subscription_history.record(
+            subscription.user,
+            ctx.bumps.subscription_history,
+            SubscriptionHistoryEntry { action: SUB_ACTION_TOPUP, tier, price: charge, timestamp: now },
+        );
+
+        msg!("Subscription topped up: user={} tier={} days={} expires={} charged={}",
+            subscription.user, tier, days, new_expiry, charge);
+        Ok(())
+    }
+
+    /// Pay for `recipient`'s subscription at `tier`. A new subscription is
+    /// created for them, or an existing one is renewed exactly as
+    /// renew_subscription would.
+    pub fn gift_subscription(ctx: Context<GiftSubscription>, recipient: Pubkey, tier: u8) -> Result<()> {
+        check_version(ctx.accounts.subscription_config.version)?;
+        require!(!ctx.accounts.subscription_config.paused, ErrorCode::SubscriptionsPaused);
+        ctx.accounts.subscription_config.check_tier(tier)?;
+        require_keys_neq!(recipient, Pubkey::default(), ErrorCode::InvalidRecipient);
+        require_keys_neq!(ctx.accounts.treasury.key(), ctx.accounts.payer.key(), ErrorCode::TreasuryIsUser);
+        require!(!ctx.accounts.subscription.lifetime, ErrorCode::LifetimeSubscription);
+
+        let now = Clock::get()?.unix_timestamp;
+        ctx.accounts.subscriber_meta.record_action(
+            ctx.accounts.payer.key(),
+            ctx.bumps.subscriber_meta,
+            ctx.accounts.subscription_config.subscribe_cooldown,
+            now,
+        )?;
+        ctx.accounts.subscription.apply_pending_tier(now);
+        let is_new = ctx.accounts.subscription.user == Pubkey::default();
+        if !is_new {
+            check_version(ctx.accounts.subscription.version)?;
+        }
+        let (mut charge, _credit) = if is_new {
+            (ctx.accounts.subscription_config.current_price(tier, now)?, 0)
+        } else {
+            ctx.accounts.subscription.renewal_charge(&ctx.accounts.subscription_config, tier, now)?
+        };
+        // Renewing a lapsed paid subscription pays the late surcharge, as in renew_subscription
+        let subscription = &ctx.accounts.subscription;
+        if !is_new && !subscription.is_trial && subscription.tier != 0 && subscription.expires_at < now {
+            charge = ctx.accounts.subscription_config.with_late_penalty(charge)?;
+        }
+
+        // Transfer SOL from payer to treasury
+        transfer_sol_verified(
+            &ctx.accounts.payer.to_account_info(),
+            &ctx.accounts.treasury.to_account_info(),
+            charge,
+        )?;
+        emit!(TreasuryInflow {
+            source: ctx.accounts.payer.key(),
+            amount: charge,
+            reason: INFLOW_GIFT,
+            timestamp: now,
+        });
+
+        let config = &ctx.accounts.subscription_config;
+        let subscription = &mut ctx.accounts.subscription;
+        let was_counted = !is_new && !subscription.is_trial && subscription.tier != 0;
+        if is_new {
+            subscription.user = recipient;
+            subscription.tier = tier;
+            subscription.accrue_active_time(now)?;
+            subscription.expires_at = now.checked_add(config.tier_duration(tier)?).ok_or(ErrorCode::ArithmeticOverflow)?;
+            subscription.created_at = now;
+            subscription.total_paid = charge;
+            subscription.auto_renew = false;
+            subscription.is_trial = false;
+            subscription.version = CURRENT_VERSION;
+            subscription.bump = ctx.bumps.subscription;
+        } else {
+            subscription.apply_renewal(config, tier, charge, now)?;
+        }
+
+        let config = &mut ctx.accounts.subscription_config;
+        if !was_counted {
+            config.add_subscriber(tier)?;
+        }
+        config.add_revenue(tier, charge)?;
+
+        emit!(SubscriptionGifted {
+            subscription: subscription.key(),
+            gifter: ctx.accounts.payer.key(),
+            recipient,
+            tier,
+            price: charge,
+            expires_at: subscription.expires_at,
+        });
+
+        msg!("Subscription gifted: gifter={} recipient={} tier={} expires={}",
+            ctx.accounts.payer.key(), recipient, tier, subscription.expires_at);
+        Ok(())
+    }
+
+    /// Whitelisted wallet: Claim a free subscription at the entry's tier, once
+    /// per entry. An active subscription is extended by the tier duration and
+    /// switched to that tier; otherwise the new cycle starts now.
+    pub fn claim_free_subscription(ctx: Context<ClaimFreeSubscription>) -> Result<()> {
+        check_version(ctx.accounts.subscription_config.version)?;
+        require!(!ctx.accounts.whitelist.claimed, ErrorCode::WhitelistAlreadyClaimed);
+        require!(!ctx.accounts.subscription.lifetime, ErrorCode::LifetimeSubscription);
+
+        let now = Clock::get()?.unix_timestamp;
+        let tier = ctx.accounts.whitelist.tier;
+        grant_free_subscription(
+            &mut ctx.accounts.subscription,
+            &mut ctx.accounts.subscription_config,
+            ctx.accounts.user.key(),
+            ctx.bumps.subscription,
+            tier,
+            now,
+        )?;
+        ctx.accounts.whitelist.claimed = true;
+
+        let subscription = &ctx.accounts.subscription;
+        msg!("Free subscription claimed: user={} tier={} expires={}",
+            subscription.user, tier, subscription.expires_at);
+        Ok(())
+    }
+
+    /// Claim a free subscription at `tier` by proving `(user, tier)` is in the
+    /// config's Merkle whitelist, once per wallet per root. Granted exactly as
+    /// claim_free_subscription would.
+    /// Leaves are `keccak(user || tier)`; proof nodes are hashed in sorted pairs.
+    pub fn claim_whitelisted(
+        ctx: Context<ClaimWhitelisted>,
+        tier: u8,
+        proof: Vec<[u8; 32]>,
+    ) -> Result<()> {
+        check_version(ctx.accounts.subscription_config.version)?;
+        require!(ctx.accounts.merkle_claim.claimed_at == 0, ErrorCode::AlreadyClaimed);
+        require!(!ctx.accounts.subscription.lifetime, ErrorCode::LifetimeSubscription);
+
+        let user = ctx.accounts.user.key();
+        let root = ctx.accounts.subscription_config.whitelist_root;
+        require!(root != [0; 32], ErrorCode::InvalidProof);
+        require!(proof.len() <= MAX_MERKLE_PROOF_LEN, ErrorCode::InvalidProof);
+        let leaf = anchor_lang::solana_program::keccak::hashv(&[user.as_ref(), &[tier]]).to_bytes();
+        require!(verify_merkle_proof(&proof, root, leaf), ErrorCode::InvalidProof);
+
+        let now = Clock::get()?.unix_timestamp;
+        grant_free_subscription(
+            &mut ctx.accounts.subscription,
+            &mut ctx.accounts.subscription_config,
+            user,
+            ctx.bumps.subscription,
+            tier,
+            now,
+        )?;
+
+        let claim = &mut ctx.accounts.merkle_claim;
+        claim.user = user;
+        claim.root = root;
+        claim.tier = tier;
+        claim.claimed_at = now;
+        claim.bump = ctx.bumps.merkle_claim;
+
+        msg!("Whitelist claimed: user={} tier={} expires={}",
+            user, tier, ctx.accounts.subscription.expires_at);
+        Ok(())
+    }
+
+    /// Watch `token_mint` for report updates, which emit WatchedTokenUpdated.
+    /// Requires an active subscription (grace period included); up to
+    /// MAX_WATCHLIST_LEN mints. Watching a mint twice is a no-op.
+    pub fn add_to_watchlist(ctx: Context<AddToWatchlist>, token_mint: Pubkey) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let subscription = &ctx.accounts.subscription;
+        let active_until = subscription.expires_at.saturating_add(ctx.accounts.subscription_config.grace_period);
+        require!(subscription.tier != 0 && active_until > now, ErrorCode::InsufficientSubscription);
+
+        let watchlist = &mut ctx.accounts.watchlist;
+        watchlist.user = ctx.accounts.user.key();
+        watchlist.bump = ctx.bumps.watchlist;
+        if !watchlist.token_mints.contains(&token_mint) {
+            require!(watchlist.token_mints.len() < MAX_WATCHLIST_LEN, ErrorCode::WatchlistFull);
+            watchlist.token_mints.push(token_mint);
+        }
+
+        msg!("Watchlist add: user={} token_mint={} watched={}",
+            watchlist.user, token_mint, watchlist.token_mints.len());
+        Ok(())
+    }
+
+    /// Stop watching `token_mint`. No subscription needed.
+    pub fn remove_from_watchlist(ctx: Context<RemoveFromWatchlist>, token_mint: Pubkey) -> Result<()> {
+        let watchlist = &mut ctx.accounts.watchlist;
+        watchlist.token_mints.retain(|m| *m != token_mint);
+
+        msg!("Watchlist remove: user={} token_mint={} watched={}",
+            watchlist.user, token_mint, watchlist.token_mints.len());
+        Ok(())
+    }
+
+    /// Deposit `amount` lamports into the signer's escrow for `crank_renew` to draw on.
+    pub fn fund_escrow(ctx: Context<FundEscrow>, amount: u64) -> Result<()> {
+        check_version(ctx.accounts.subscription_config.version)?;
+        require!(!ctx.accounts.subscription_config.paused, ErrorCode::SubscriptionsPaused);
+
+        transfer_sol_verified(
+            &ctx.accounts.user.to_account_info(),
+            &ctx.accounts.escrow.to_account_info(),
+            amount,
+        )?;
+
+        let escrow = &mut ctx.accounts.escrow;
+        escrow.user = ctx.accounts.user.key();
+        escrow.balance = escrow.balance.checked_add(amount).ok_or(ErrorCode::ArithmeticOverflow)?;
+        escrow.bump = ctx.bumps.escrow;
+
+        msg!("Escrow funded: user={} amount={} balance={}", escrow.user, amount, escrow.balance);
+        Ok(())
+    }
+
+    /// Withdraw unused escrow lamports back to the user. The escrow keeps its rent.
+    pub fn withdraw_escrow(ctx: Context<WithdrawEscrow>, amount: u64) -> Result<()> {
+        check_version(ctx.accounts.subscription_config.version)?;
+        require!(!ctx.accounts.subscription_config.paused, ErrorCode::SubscriptionsPaused);
+        require!(amount <= ctx.accounts.escrow.balance, ErrorCode::InsufficientEscrowBalance);
+
+        let escrow_info = ctx.accounts.escrow.to_account_info();
+        let rent_floor = Rent::get()?.minimum_balance(escrow_info.data_len());
+        require!(
+            escrow_info.lamports().saturating_sub(amount) >= rent_floor,
+            ErrorCode::EscrowUnderRent
+        );
+        move_lamports(&escrow_info, &ctx.accounts.user.to_account_info(), amount)?;
+
+        let escrow = &mut ctx.accounts.escrow;
+        escrow.balance = escrow.balance.checked_sub(amount).ok_or(ErrorCode::ArithmeticOverflow)?;
+
+        msg!("Escrow withdrawn: user={} amount={} balance={}", escrow.user, amount, escrow.balance);
+        Ok(())
+    }
+
+    /// Opt in or out of automatic renewal via `crank_renew`.
+    pub fn set_auto_renew(ctx: Context<SetAutoRenew>, auto_renew: bool) -> Result<()> {
+        check_version(ctx.accounts.subscription.version)?;
+        let subscription = &mut ctx.accounts.subscription;
+        subscription.auto_renew = auto_renew;
+        if auto_renew {
+            subscription.disabled = false;
+        }
+
+        msg!("Auto-renew set: user={} auto_renew={}", subscription.user, auto_renew);
+        Ok(())
+    }
+
+    /// Mark the subscription as churned and switch off auto-renew. No refund;
+    /// access continues until it expires.
+    pub fn disable_subscription(ctx: Context<DisableSubscription>) -> Result<()> {
+        check_version(ctx.accounts.subscription.version)?;
+        let subscription = &mut ctx.accounts.subscription;
+        subscription.auto_renew = false;
+        subscription.disabled = true;
+
+        msg!("Subscription disabled: user={} tier={} expires={}",
+            subscription.user, subscription.tier, subscription.expires_at);
+        Ok(())
+    }
+
+    /// Renew an expired auto-renew subscription at its current tier, paid from
+    /// the user's escrow PDA. Permissionless so anyone can crank it.
+    /// If the escrow can't cover that price, it renews at the highest lower tier
+    /// it can cover; if it covers none, auto-renew is switched off instead of failing.
+    pub fn crank_renew(ctx: Context<CrankRenew>) -> Result<()> {
+        check_version(ctx.accounts.subscription_config.version)?;
+        check_version(ctx.accounts.subscription.version)?;
+        require!(!ctx.accounts.subscription_config.paused, ErrorCode::SubscriptionsPaused);
+        let now = Clock::get()?.unix_timestamp;
+        ctx.accounts.subscription.apply_pending_tier(now);
+        let subscription = &ctx.accounts.subscription;
+        let config = &ctx.accounts.subscription_config;
+        require!(subscription.auto_renew, ErrorCode::AutoRenewDisabled);
+        require!(subscription.expires_at <= now, ErrorCode::SubscriptionNotExpired);
+
+        let old_tier = subscription.tier;
+        let available = ctx.accounts.escrow.balance;
+        let affordable = (1..=old_tier)
+            .rev()
+            .find(|&tier| config.tier_price(tier).is_ok_and(|price| price <= available));
+        let Some(tier) = affordable else {
+            let price = config.tier_price(old_tier)?;
+            let subscription = &mut ctx.accounts.subscription;
+            subscription.auto_renew = false;
+            msg!("Auto-renew disabled: user={} escrow={} price={}",
+                subscription.user, available, price);
+            return Ok(());
+        };
+        let price = config.tier_price(tier)?;
+
+        // Pay from escrow to treasury
+        let user = subscription.user;
+        move_lamports(
+            &ctx.accounts.escrow.to_account_info(),
+            &ctx.accounts.treasury.to_account_info(),
+            price,
+        )?;
+        let escrow = &mut ctx.accounts.escrow;
+        escrow.balance = escrow.balance.checked_sub(price).ok_or(ErrorCode::ArithmeticOverflow)?;
+        emit!(TreasuryInflow {
+            source: user,
+            amount: price,
+            reason: INFLOW_AUTO_RENEW,
+            timestamp: now,
+        });
+
+        let new_expiry = now.checked_add(config.tier_duration(tier)?).ok_or(ErrorCode::ArithmeticOverflow)?;
+        let subscription = &mut ctx.accounts.subscription;
+        let was_trial = subscription.is_trial;
+        subscription.accrue_active_time(now)?;
+        subscription.tier = tier;
+        subscription.pending_tier = 0;
+        subscription.pending_tier_at = 0;
+        subscription.expires_at = new_expiry;
+        subscription.total_paid = subscription.total_paid.checked_add(price).ok_or(ErrorCode::ArithmeticOverflow)?;
+        subscription.is_trial = false;
+
+        let config = &mut ctx.accounts.subscription_config;
+        if was_trial {
+            config.add_subscriber(tier)?;
+        }
+        config.add_revenue(tier, price)?;
+
+        emit!(SubscriptionRenewed {
+            subscription: subscription.key(),
+            user,
+            old_tier,
+            new_tier: tier,
+            price,
+            expires_at: new_expiry,
+            late_penalty: false,
+        });
+
+        msg!("Subscription auto-renewed: user={} old_tier={} tier={} expires={}",
+            user, old_tier, tier, new_expiry);
+        Ok(())
+    }
+
+    /// Cancel a subscription and refund the unused portion from the treasury PDA.
+    /// Refund = remaining / tier duration * total_paid, capped at total_paid.
+    /// With a revenue split set, payments bypass the treasury, so the admin must
+    /// keep it funded for refunds; an underfunded treasury fails with TreasuryUnderRent.
+    pub fn cancel_subscription(ctx: Context<CancelSubscription>) -> Result<()> {
+        check_version(ctx.accounts.subscription_config.version)?;
+        check_version(ctx.accounts.subscription.version)?;
+        require!(!ctx.accounts.subscription_config.paused, ErrorCode::SubscriptionsPaused);
+        let now = Clock::get()?.unix_timestamp;
+        ctx.accounts.subscriber_meta.record_action(
+            ctx.accounts.user.key(),
+            ctx.bumps.subscriber_meta,
+            ctx.accounts.subscription_config.subscribe_cooldown,
+            now,
+        )?;
+        let subscription = &ctx.accounts.subscription;
+        let config = &ctx.accounts.subscription_config;
+        require!(subscription.tier != 0, ErrorCode::SubscriptionAlreadyCancelled);
+        require!(!subscription.lifetime, ErrorCode::LifetimeSubscription);
+
+        let in_window = now.saturating_sub(subscription.created_at) <= config.refund_window;
+        let refund = if config.refund_mode != REFUND_MODE_PRORATED && in_window {
+            subscription.total_paid
+        } else {
+            require!(config.refund_mode != REFUND_MODE_WINDOW, ErrorCode::RefundWindowClosed);
+            let remaining = subscription.expires_at.saturating_sub(now);
+            let duration = config.tier_duration(subscription.tier)?;
+            prorated_amount(subscription.total_paid, remaining, duration)
+                .min(subscription.total_paid)
+        };
+
+        if refund > 0 {
+            let treasury = &ctx.accounts.treasury;
+            assert_treasury_solvent(treasury, refund)?;
+
+            let seeds: &[&[u8]] = &[b"treasury".as_ref(), &[config.treasury_bump]];
+            let ix = anchor_lang::solana_program::system_instruction::transfer(
+                &treasury.key(),
+                &ctx.accounts.user.key(),
+                refund,
+            );
+            anchor_lang::solana_program::program::invoke_signed(
+                &ix,
+                &[
+                    treasury.to_account_info(),
+                    ctx.accounts.user.to_account_info(),
+                ],
+                &[seeds],
+            )?;
+        }
+
+        ctx.accounts.subscription_history.record(
+            subscription.user,
+            ctx.bumps.subscription_history,
+            SubscriptionHistoryEntry {
+                action: SUB_ACTION_CANCEL,
+                tier: subscription.tier,
+                price: refund,
+                timestamp: now,
+            },
+        );
+
+        let subscription = &mut ctx.accounts.subscription;
+        let was_trial = subscription.is_trial;
+        subscription.tier = 0;
+        subscription.expires_at = now;
+        subscription.total_paid = subscription.total_paid.checked_sub(refund).ok_or(ErrorCode::ArithmeticOverflow)?;
+        subscription.is_trial = false;
+        subscription.pending_tier = 0;
+        subscription.pending_tier_at = 0;
+
+        // Trials are not counted as subscribers
+        let config = &mut ctx.accounts.subscription_config;
+        if !was_trial {
+            config.total_subscribers = config.total_subscribers.checked_sub(1).ok_or(ErrorCode::ArithmeticOverflow)?;
+        }
+        config.total_revenue = config.total_revenue.saturating_sub(refund);
+
+        msg!("Subscription cancelled: user={} refund={}", subscription.user, refund);
+        Ok(())
+    }
+
+    /// Move the signer's subscription to `new_owner`, closing the old account and
+    /// returning its rent. Rejected if `new_owner` already has a subscription
+    /// account (active or not) rather than merging; a lapsed one can be closed
+    /// first with close_subscription. Auto-renew is switched off since the
+    /// escrow belongs to the old wallet.
+    pub fn transfer_subscription(ctx: Context<TransferSubscription>, new_owner: Pubkey) -> Result<()> {
+        check_version(ctx.accounts.subscription.version)?;
+        require_keys_neq!(new_owner, ctx.accounts.user.key(), ErrorCode::SelfTransfer);
+        require_keys_neq!(new_owner, Pubkey::default(), ErrorCode::InvalidRecipient);
+
+        let old = &ctx.accounts.subscription;
+        let new = &mut ctx.accounts.new_subscription;
+        new.user = new_owner;
+        new.tier = old.tier;
+        new.expires_at = old.expires_at;
+        new.created_at = old.created_at;
+        new.total_paid = old.total_paid;
+        new.auto_renew = false;
+        new.is_trial = old.is_trial;
+        new.pending_tier = old.pending_tier;
+        new.pending_tier_at = old.pending_tier_at;
+        new.lifetime = old.lifetime;
+        new.disabled = old.disabled;
+        new.receipt_mint = old.receipt_mint;
+        new.first_subscribed_at = old.first_subscribed_at;
+        new.total_active_seconds = old.total_active_seconds;
+        new.active_since = old.active_since;
+        new.version = CURRENT_VERSION;
+        new.bump = ctx.bumps.new_subscription;
+
+        msg!("Subscription transferred: from={} to={} tier={} expires={}",
+            old.user, new_owner, new.tier, new.expires_at);
+        Ok(())
+    }
+
+    /// Close a subscription whose grace period has ended, returning its rent to
+    /// the user. Permissionless, so anyone can clean up lapsed accounts.
+    pub fn close_subscription(ctx: Context<CloseSubscription>) -> Result<()> {
+        check_version(ctx.accounts.subscription_config.version)?;
+        let now = Clock::get()?.unix_timestamp;
+        let subscription = &ctx.accounts.subscription;
+        let config = &mut ctx.accounts.subscription_config;
+
+        let active_until = subscription.expires_at.saturating_add(config.grace_period);
+        require!(active_until < now, ErrorCode::SubscriptionStillActive);
+
+        // Cancelled subscriptions and trials were already uncounted
+        if subscription.tier != 0 && !subscription.is_trial {
+            config.total_subscribers = config.total_subscribers.checked_sub(1).ok_or(ErrorCode::ArithmeticOverflow)?;
+        }
+
+        msg!("Subscription closed: user={}", subscription.user);
+        Ok(())
+    }
+
+    /// Verify subscription status.
+    /// A subscription stays active for `grace_period` seconds past `expires_at`;
+    /// a grace period of 0 means access ends exactly at expiry.
+    /// A scheduled downgrade is applied first once its effective time has passed.
+    /// Returns a `SubscriptionStatus` (Anchor writes it with `set_return_data`, so
+    /// CPI callers can read it with `get_return_data`). With `strict`, an
+    /// unverified subscription fails the instruction instead.
+    pub fn verify_subscription(
+        ctx: Context<VerifySubscription>,
+        required_tier: u8,
+        strict: bool,
+    ) -> Result<SubscriptionStatus> {
+        let clock = Clock::get()?;
+        ctx.accounts.subscription.apply_pending_tier(clock.unix_timestamp);
+        let subscription = &ctx.accounts.subscription;
+        let config = &ctx.accounts.subscription_config;
+
+        let active_until = subscription.expires_at.saturating_add(config.grace_period);
+        let is_active = active_until > clock.unix_timestamp;
+        let remaining = active_until.saturating_sub(clock.unix_timestamp).max(0);
+        let has_tier = subscription.tier >= required_tier;
+        let verified = is_active && has_tier;
+
+        msg!("Subscription verification: user={} tier={} active={} remaining={}s verified={}",
+            subscription.user, subscription.tier, is_active, remaining, verified);
+        
+        if strict {
+            require!(verified, ErrorCode::InsufficientSubscription);
+        }
+        Ok(SubscriptionStatus {
+            verified,
+            tier: subscription.tier,
+            expires_at: subscription.expires_at,
+        })
+    }
+
+    /// Report the live status of each Subscription in remaining_accounts, as
+    /// return data in the same order. Expired subscriptions are reported as
+    /// inactive rather than failing the call.
+    pub fn verify_subscriptions_batch<'info>(
+        ctx: Context<'_, '_, 'info, 'info, VerifySubscriptionsBatch<'info>>,
+    ) -> Result<Vec<SubscriptionSummary>> {
+        let accounts = ctx.remaining_accounts;
+        require!(
+            !accounts.is_empty() && accounts.len() <= MAX_VERIFY_BATCH,
+            ErrorCode::InvalidBatchSize
+        );
+
+        let now = Clock::get()?.unix_timestamp;
+        let grace_period = ctx.accounts.subscription_config.grace_period;
+        let mut results = Vec::with_capacity(accounts.len());
+        for info in accounts {
+            let subscription: Account<Subscription> = Account::try_from(info)?;
+            results.push(SubscriptionSummary {
+                user: subscription.user,
+                tier: subscription.current_tier(now),
+                active: subscription.expires_at.saturating_add(grace_period) > now,
+            });
+        }
+
+        let active = results.iter().filter(|r| r.active).count();
+        msg!("Subscriptions verified: count={} active={}", results.len(), active);
+        Ok(results)
+    }
+
+    /// Return current pricing (Anchor writes it with `set_return_data`), so
+    /// clients and CPI callers can price a subscribe tx without the IDL.
+    pub fn get_pricing(ctx: Context<GetPricing>) -> Result<PricingView> {
+        let config = &ctx.accounts.subscription_config;
+        let count = config.tier_count as usize;
+        Ok(PricingView {
+            tier_prices: config.tier_prices[..count].to_vec(),
+            tier_durations: config.tier_durations[..count].to_vec(),
+            lifetime_price: config.lifetime_price,
+            promo_prices: config.promo_prices.clone(),
+            promo_ends_at: config.promo_ends_at,
+            grace_period: config.grace_period,
+            paused: config.paused,
+        })
+    }
+
+    /// Admin: Update subscription pricing. `tier_prices[i]` prices tier `i + 1`;
+    /// a tier is offered once it has both a price and a duration (see update_durations).
+    pub fn update_pricing(
+        ctx: Context<UpdatePricing>,
+        tier_prices: Vec<u64>,
+        lifetime_price: u64,
+    ) -> Result<()> {
+        check_version(ctx.accounts.subscription_config.version)?;
+        ctx.accounts.subscription_config.record_admin_action()?;
+        validate_pricing(&tier_prices)?;
+
+        let config = &mut ctx.accounts.subscription_config;
+        config.tier_prices = tier_prices;
+        config.lifetime_price = lifetime_price;
+        config.refresh_tier_count();
+
+        msg!("Pricing updated: tiers={:?} lifetime={} tier_count={}",
+            config.tier_prices, lifetime_price, config.tier_count);
+        Ok(())
+    }
+
+    /// Admin: Run a promotion until `promo_ends_at`. `promo_prices[i]` replaces
+    /// tier `i + 1`'s price for subscribe and renewals and must be below it.
+    /// An empty list clears the promotion.
+    pub fn set_promo(ctx: Context<UpdatePricing>, promo_prices: Vec<u64>, promo_ends_at: i64) -> Result<()> {
+        check_version(ctx.accounts.subscription_config.version)?;
+        ctx.accounts.subscription_config.record_admin_action()?;
+        let config = &mut ctx.accounts.subscription_config;
+        require!(promo_prices.len() <= config.tier_count as usize, ErrorCode::InvalidTierCount);
+        require!(
+            promo_prices.iter().zip(&config.tier_prices).all(|(promo, price)| promo < price),
+            ErrorCode::InvalidPromoPrice
+        );
+
+        config.promo_prices = promo_prices;
+        config.promo_ends_at = promo_ends_at;
+
+        msg!("Promo set: prices={:?} ends_at={}", config.promo_prices, promo_ends_at);
+        Ok(())
+    }
+
+    /// Admin: Accept an SPL mint for subscribe_spl at the given per-tier prices
+    /// (mint base units, index 0 = tier 1). Tiers past the end of `tier_prices`
+    /// cannot be bought with this mint.
+    /// To change prices, remove the mint and add it again.
+    pub fn add_accepted_mint(ctx: Context<AddAcceptedMint>, tier_prices: Vec<u64>) -> Result<()> {
+        check_version(ctx.accounts.subscription_config.version)?;
+        require!(!tier_prices.is_empty() && tier_prices.len() <= MAX_TIERS, ErrorCode::InvalidTierCount);
+        ctx.accounts.subscription_config.record_admin_action()?;
+        let accepted_mint = &mut ctx.accounts.accepted_mint;
+        accepted_mint.mint = ctx.accounts.mint.key();
+        accepted_mint.tier_prices = tier_prices;
+        accepted_mint.total_revenue = 0;
+        accepted_mint.bump = ctx.bumps.accepted_mint;
+
+        msg!("Accepted mint added: mint={} prices={:?}",
+            accepted_mint.mint, accepted_mint.tier_prices);
+        Ok(())
+    }
+
+    /// Admin: Stop accepting an SPL mint. Rent is returned to the admin.
+    pub fn remove_accepted_mint(ctx: Context<RemoveAcceptedMint>) -> Result<()> {
+        ctx.accounts.subscription_config.record_admin_action()?;
+        msg!("Accepted mint removed: {}", ctx.accounts.accepted_mint.mint);
+        Ok(())
+    }
+
+    /// Admin: Withdraw SOL from the treasury PDA to any destination.
+    pub fn withdraw_treasury(ctx: Context<WithdrawTreasury>, amount: u64) -> Result<()> {
+        ctx.accounts.subscription_config.record_admin_action()?;
+        require!(!ctx.accounts.subscription_config.paused, ErrorCode::SubscriptionsPaused);
+        let treasury = &ctx.accounts.treasury;
+        assert_treasury_solvent(treasury, amount)?;
+
+        let bump = ctx.accounts.subscription_config.treasury_bump;
+        let seeds: &[&[u8]] = &[b"treasury".as_ref(), &[bump]];
+
+        let ix = anchor_lang::solana_program::system_instruction::transfer(
+            &treasury.key(),
+            &ctx.accounts.destination.key(),
+            amount,
+        );
+        anchor_lang::solana_program::program::invoke_signed(
+            &ix,
+            &[
+                treasury.to_account_info(),
+                ctx.accounts.destination.to_account_info(),
+            ],
+            &[seeds],
+        )?;
+
+        msg!("Treasury withdrawal: amount={} destination={}",
+            amount, ctx.accounts.destination.key());
+        Ok(())
+    }
+
+    /// Admin: Update the post-expiry grace period (seconds).
+    pub fn update_grace_period(ctx: Context<AdminConfig>, grace_period: i64) -> Result<()> {
+        check_version(ctx.accounts.subscription_config.version)?;
+        ctx.accounts.subscription_config.record_admin_action()?;
+        require!(grace_period >= 0, ErrorCode::InvalidGracePeriod);
+
+        let config = &mut ctx.accounts.subscription_config;
+        config.grace_period = grace_period;
+
+        msg!("Grace period updated: {}s", grace_period);
+        Ok(())
+    }
+
+    /// Admin: Set the referrer's share of referred subscriptions (basis points).
+    pub fn update_referral_bps(ctx: Context<AdminConfig>, referral_bps: u16) -> Result<()> {
+        check_version(ctx.accounts.subscription_config.version)?;
+        ctx.accounts.subscription_config.record_admin_action()?;
+        require!(referral_bps <= 10_000, ErrorCode::InvalidReferralBps);
+
+        let config = &mut ctx.accounts.subscription_config;
+        config.referral_bps = referral_bps;
+
+        msg!("Referral share updated: {} bps", referral_bps);
+        Ok(())
+    }
+
+    /// Admin: Set the subscribe_annual discount (basis points, below 10_000).
+    pub fn update_annual_discount(ctx: Context<AdminConfig>, annual_discount_bps: u16) -> Result<()> {
+        check_version(ctx.accounts.subscription_config.version)?;
+        ctx.accounts.subscription_config.record_admin_action()?;
+        require!(annual_discount_bps < 10_000, ErrorCode::InvalidDiscount);
+
+        let config = &mut ctx.accounts.subscription_config;
+        config.annual_discount_bps = annual_discount_bps;
+
+        msg!("Annual discount updated: {} bps", annual_discount_bps);
+        Ok(())
+    }
+
+    /// Admin: Set the surcharge on renewing a lapsed subscription (basis points, at most 10_000).
+    pub fn update_late_penalty(ctx: Context<AdminConfig>, late_penalty_bps: u16) -> Result<()> {
+        check_version(ctx.accounts.subscription_config.version)?;
+        ctx.accounts.subscription_config.record_admin_action()?;
+        require!(late_penalty_bps <= 10_000, ErrorCode::InvalidLatePenalty);
+
+        let config = &mut ctx.accounts.subscription_config;
+        config.late_penalty_bps = late_penalty_bps;
+
+        msg!("Late renewal penalty updated: {} bps", late_penalty_bps);
+        Ok(())
+    }
+
+    /// Admin: Set the minimum seconds between a wallet's subscribes and cancels (0 = no limit).
+    pub fn update_subscribe_cooldown(ctx: Context<AdminConfig>, subscribe_cooldown: i64) -> Result<()> {
+        check_version(ctx.accounts.subscription_config.version)?;
+        ctx.accounts.subscription_config.record_admin_action()?;
+        require!(subscribe_cooldown >= 0, ErrorCode::InvalidSubscribeCooldown);
+
+        let config = &mut ctx.accounts.subscription_config;
+        config.subscribe_cooldown = subscribe_cooldown;
+
+        msg!("Subscribe cooldown updated: {}s", subscribe_cooldown);
+        Ok(())
+    }
+
+    /// Emergency switch freezing every instruction that moves funds
+    /// (subscribe*, renew, crank_renew, cancel, withdraw_treasury) (admin only).
+    /// verify_subscription and start_trial keep working while paused.
+    pub fn set_subscription_paused(ctx: Context<AdminConfig>, paused: bool) -> Result<()> {
+        check_version(ctx.accounts.subscription_config.version)?;
+        ctx.accounts.subscription_config.record_admin_action()?;
+        let config = &mut ctx.accounts.subscription_config;
+        config.paused = paused;
+
+        emit!(SubscriptionsPauseChanged {
+            paused,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        msg!("Subscriptions paused: {}", paused);
+        Ok(())
+    }
+
+    /// Admin: Set how long the admin may be inactive before anyone can pause
+    /// subscriptions with auto_pause_if_stale. 0 disables the switch.
+    pub fn update_inactivity_timeout(ctx: Context<AdminConfig>, inactivity_timeout: i64) -> Result<()> {
+        check_version(ctx.accounts.subscription_config.version)?;
+        ctx.accounts.subscription_config.record_admin_action()?;
+        require!(inactivity_timeout >= 0, ErrorCode::InvalidInactivityTimeout);
+
+        let config = &mut ctx.accounts.subscription_config;
+        config.inactivity_timeout = inactivity_timeout;
+
+        msg!("Inactivity timeout updated: {}s", inactivity_timeout);
+        Ok(())
+    }
+
+    /// Dead man's switch: pause subscriptions once no admin instruction has run
+    /// for `inactivity_timeout` seconds. Anyone may call it. Only the admin can
+    /// unpause, which also resets the timer.
+    pub fn auto_pause_if_stale(ctx: Context<AutoPauseIfStale>) -> Result<()> {
+        check_version(ctx.accounts.subscription_config.version)?;
+        let config = &mut ctx.accounts.subscription_config;
+        require!(config.inactivity_timeout > 0, ErrorCode::AdminStillActive);
+        let now = Clock::get()?.unix_timestamp;
+        require!(
+            now.saturating_sub(config.last_admin_action) > config.inactivity_timeout,
+            ErrorCode::AdminStillActive
+        );
+        if config.paused {
+            msg!("Subscriptions already paused");
+            return Ok(());
+        }
+        config.paused = true;
+
+        emit!(SubscriptionsPauseChanged {
+            paused: true,
+            timestamp: now,
+        });
+
+        msg!("Subscriptions auto-paused: last_admin_action={} timeout={}s",
+            config.last_admin_action, config.inactivity_timeout);
+        Ok(())
+    }
+
+    /// Create a discount coupon (admin only). `code_hash` is `keccak(code)` of the
+    /// promo code, so the plain code never appears in account data; subscribe
+    /// derives the coupon from the code it is given.
+    pub fn create_coupon(
+        ctx: Context<CreateCoupon>,
+        code_hash: [u8; 32],
+        discount_bps: u16,
+        max_uses: u32,
+        expires_at: i64,
+    ) -> Result<()> {
+        check_version(ctx.accounts.subscription_config.version)?;
+        ctx.accounts.subscription_config.record_admin_action()?;
+        require!(discount_bps <= 10_000, ErrorCode::InvalidDiscount);
+        require!(expires_at > Clock::get()?.unix_timestamp, ErrorCode::CouponExpired);
+
+        let coupon = &mut ctx.accounts.coupon;
+        coupon.code_hash = code_hash;
+        coupon.discount_bps = discount_bps;
+        coupon.max_uses = max_uses;
+        coupon.uses = 0;
+        coupon.expires_at = expires_at;
+        coupon.bump = ctx.bumps.coupon;
+
+        msg!("Coupon created: discount={} bps max_uses={} expires={}",
+            discount_bps, max_uses, expires_at);
+        Ok(())
+    }
+
+    /// Whitelist `user` for one free subscription at `tier` (admin only).
+    pub fn add_to_whitelist(ctx: Context<AddToWhitelist>, user: Pubkey, tier: u8) -> Result<()> {
+        ctx.accounts.subscription_config.record_admin_action()?;
+        ctx.accounts.subscription_config.check_tier(tier)?;
+
+        let whitelist = &mut ctx.accounts.whitelist;
+        whitelist.user = user;
+        whitelist.tier = tier;
+        whitelist.claimed = false;
+        whitelist.added_at = Clock::get()?.unix_timestamp;
+        whitelist.bump = ctx.bumps.whitelist;
+
+        msg!("Whitelisted: user={} tier={}", user, tier);
+        Ok(())
+    }
+
+    /// Remove `user`'s whitelist entry, claimed or not (admin only).
+    pub fn remove_from_whitelist(ctx: Context<RemoveFromWhitelist>, user: Pubkey) -> Result<()> {
+        ctx.accounts.subscription_config.record_admin_action()?;
+        msg!("Removed from whitelist: user={} claimed={}", user, ctx.accounts.whitelist.claimed);
+        Ok(())
+    }
+
+    /// Set the cycle length of each tier (admin only).
+    /// Applies to purchases and renewals from now on; existing expiries are unchanged.
+    pub fn update_durations(ctx: Context<AdminConfig>, tier_durations: Vec<i64>) -> Result<()> {
+        check_version(ctx.accounts.subscription_config.version)?;
+        ctx.accounts.subscription_config.record_admin_action()?;
+        validate_durations(&tier_durations)?;
+
+        let config = &mut ctx.accounts.subscription_config;
+        config.tier_durations = tier_durations;
+        config.refresh_tier_count();
+
+        msg!("Durations updated: tiers={:?} tier_count={}",
+            config.tier_durations, config.tier_count);
+        Ok(())
+    }
+
+    /// Set how subscribe and renew_subscription payments are split (admin only).
+    /// Up to 4 recipients whose `bps` must sum to 10_000. From then on those
+    /// payments must pass the split, and none of them reach the treasury PDA, so
+    /// refunds (cancel_subscription, admin_refund) need it funded separately.
+    pub fn set_revenue_split(ctx: Context<SetRevenueSplit>, entries: Vec<SplitEntry>) -> Result<()> {
+        check_version(ctx.accounts.subscription_config.version)?;
+        ctx.accounts.subscription_config.record_admin_action()?;
+        require!(
+            !entries.is_empty() && entries.len() <= MAX_SPLIT_RECIPIENTS,
+            ErrorCode::InvalidSplitCount
+        );
+        let total_bps = entries.iter().map(|e| e.bps as u32).sum::<u32>();
+        require!(total_bps == 10_000, ErrorCode::InvalidSplitBps);
+
+        let split = &mut ctx.accounts.revenue_split;
+        split.entries = entries;
+        split.bump = ctx.bumps.revenue_split;
+        ctx.accounts.subscription_config.revenue_split_set = true;
+
+        msg!("Revenue split updated: recipients={}", split.entries.len());
+        Ok(())
+    }
+
+    /// Refund `amount` lamports of `user`'s payments from the treasury PDA
+    /// (admin only), e.g. after a disputed charge. Capped at what the
+    /// subscription has paid; every refund is tallied in the user's RefundRecord.
+    pub fn admin_refund(ctx: Context<AdminRefund>, user: Pubkey, amount: u64) -> Result<()> {
+        check_version(ctx.accounts.subscription_config.version)?;
+        check_version(ctx.accounts.subscription.version)?;
+        ctx.accounts.subscription_config.record_admin_action()?;
+        require!(!ctx.accounts.subscription_config.paused, ErrorCode::SubscriptionsPaused);
+        require!(amount <= ctx.accounts.subscription.total_paid, ErrorCode::RefundExceedsPaid);
+
+        let treasury = &ctx.accounts.treasury;
+        assert_treasury_solvent(treasury, amount)?;
+
+        let bump = ctx.accounts.subscription_config.treasury_bump;
+        let seeds: &[&[u8]] = &[b"treasury".as_ref(), &[bump]];
+        let ix = anchor_lang::solana_program::system_instruction::transfer(
+            &treasury.key(),
+            &ctx.accounts.user_wallet.key(),
+            amount,
+        );
+        anchor_lang::solana_program::program::invoke_signed(
+            &ix,
+            &[
+                treasury.to_account_info(),
+                ctx.accounts.user_wallet.to_account_info(),
+            ],
+            &[seeds],
+        )?;
+
+        let subscription = &mut ctx.accounts.subscription;
+        subscription.total_paid = subscription.total_paid.checked_sub(amount).ok_or(ErrorCode::ArithmeticOverflow)?;
+
+        // total_revenue is net of referral cuts while total_paid is gross
+        let config = &mut ctx.accounts.subscription_config;
+        config.total_revenue = config.total_revenue.saturating_sub(amount);
+
+        let now = Clock::get()?.unix_timestamp;
+        let record = &mut ctx.accounts.refund_record;
+        record.user = user;
+        record.total_refunded = record.total_refunded.checked_add(amount).ok_or(ErrorCode::ArithmeticOverflow)?;
+        record.refund_count = record.refund_count.checked_add(1).ok_or(ErrorCode::ArithmeticOverflow)?;
+        record.last_amount = amount;
+        record.last_refund_at = now;
+        record.bump = ctx.bumps.refund_record;
+
+        msg!("Admin refund: user={} amount={} total_refunded={}",
+            user, amount, record.total_refunded);
+        Ok(())
+    }
+
+    /// End `user`'s subscription immediately without a refund (admin only),
+    /// e.g. for abuse. A subscription that has already expired is left as is.
+    pub fn revoke_subscription(ctx: Context<RevokeSubscription>, user: Pubkey) -> Result<()> {
+        check_version(ctx.accounts.subscription_config.version)?;
+        check_version(ctx.accounts.subscription.version)?;
+        ctx.accounts.subscription_config.record_admin_action()?;
+        let now = Clock::get()?.unix_timestamp;
+        let subscription = &mut ctx.accounts.subscription;
+        if subscription.tier == 0 || subscription.expires_at <= now {
+            msg!("Subscription already expired: user={} expires={}", user, subscription.expires_at);
+            return Ok(());
+        }
+
+        let tier = subscription.tier;
+        let was_trial = subscription.is_trial;
+        let expires_at = subscription.expires_at;
+        subscription.tier = 0;
+        subscription.expires_at = now;
+        subscription.auto_renew = false;
+        subscription.is_trial = false;
+        subscription.lifetime = false;
+        subscription.pending_tier = 0;
+        subscription.pending_tier_at = 0;
+
+        // Trials are not counted as subscribers
+        if !was_trial {
+            let config = &mut ctx.accounts.subscription_config;
+            config.total_subscribers = config.total_subscribers.checked_sub(1).ok_or(ErrorCode::ArithmeticOverflow)?;
+        }
+
+        emit!(SubscriptionRevoked {
+            subscription: subscription.key(),
+            user,
+            tier,
+            expires_at,
+            revoked_at: now,
+        });
+
+        msg!("Subscription revoked: user={} tier={} expires={}", user, tier, expires_at);
+        Ok(())
+    }
+
+    /// Set the Merkle root of `(user, tier)` leaves for claim_whitelisted
+    /// (admin only). All zeroes disables Merkle claims; a new root opens a
+    /// new round in which every listed wallet can claim once.
+    pub fn set_whitelist_root(ctx: Context<AdminConfig>, whitelist_root: [u8; 32]) -> Result<()> {
+        check_version(ctx.accounts.subscription_config.version)?;
+        ctx.accounts.subscription_config.record_admin_action()?;
+        let config = &mut ctx.accounts.subscription_config;
+        config.whitelist_root = whitelist_root;
+
+        msg!("Whitelist root set: {:?}", whitelist_root);
+        Ok(())
+    }
+
+    /// Set the length of the free trial (admin only).
+    pub fn update_trial_duration(ctx: Context<AdminConfig>, trial_duration: i64) -> Result<()> {
+        check_version(ctx.accounts.subscription_config.version)?;
+        ctx.accounts.subscription_config.record_admin_action()?;
+        require!(trial_duration > 0, ErrorCode::InvalidTrialDuration);
+
+        let config = &mut ctx.accounts.subscription_config;
+        config.trial_duration = trial_duration;
+
+        msg!("Trial duration updated: {}s", trial_duration);
+        Ok(())
+    }
+
+    /// Limit report submissions by the reporting authority's subscription tier
+    /// (admin only). `report_quotas[t]` is the number of reports allowed per
+    /// `report_quota_period` at tier `t`, where tier 0 means no active
+    /// subscription; higher tiers than listed are unlimited. An empty list
+    /// disables quotas.
+    pub fn update_report_quotas(
+        ctx: Context<AdminConfig>,
+        report_quotas: Vec<u64>,
+        report_quota_period: i64,
+    ) -> Result<()> {
+        check_version(ctx.accounts.subscription_config.version)?;
+        ctx.accounts.subscription_config.record_admin_action()?;
+        require!(report_quotas.len() <= MAX_TIERS + 1, ErrorCode::InvalidTierCount);
+        require!(report_quota_period > 0, ErrorCode::InvalidQuotaPeriod);
+
+        let config = &mut ctx.accounts.subscription_config;
+        config.report_quotas = report_quotas;
+        config.report_quota_period = report_quota_period;
+
+        msg!("Report quotas updated: quotas={:?} period={}s", config.report_quotas, report_quota_period);
+        Ok(())
+    }
+
+    /// Cap the number of subscribers, e.g. during a beta (admin only). 0 = unlimited.
+    /// Lowering it below the current count only blocks new subscribers.
+    pub fn update_max_subscribers(ctx: Context<AdminConfig>, max_subscribers: u64) -> Result<()> {
+        check_version(ctx.accounts.subscription_config.version)?;
+        ctx.accounts.subscription_config.record_admin_action()?;
+        let config = &mut ctx.accounts.subscription_config;
+        config.max_subscribers = max_subscribers;
+
+        msg!("Max subscribers updated: {} (current: {})", max_subscribers, config.total_subscribers);
+        Ok(())
+    }
+
+    /// Set the cancellation refund policy (admin only). `refund_window` is
+    /// measured from the subscription's creation; see the REFUND_MODE_* constants.
+    pub fn update_refund_policy(ctx: Context<AdminConfig>, refund_window: i64, refund_mode: u8) -> Result<()> {
+        check_version(ctx.accounts.subscription_config.version)?;
+        ctx.accounts.subscription_config.record_admin_action()?;
+        require!(
+            refund_window >= 0 && refund_mode <= REFUND_MODE_WINDOW_PRORATED,
+            ErrorCode::InvalidRefundPolicy
+        );
+
+        let config = &mut ctx.accounts.subscription_config;
+        config.refund_window = refund_window;
+        config.refund_mode = refund_mode;
+
+        msg!("Refund policy updated: window={}s mode={}", refund_window, refund_mode);
+        Ok(())
+    }
+
+    /// Admin: Route future subscribe_spl payments to token accounts owned by
+    /// `new_token_treasury`. SOL payments keep going to `treasury`.
+    pub fn set_token_treasury(ctx: Context<AdminConfig>, new_token_treasury: Pubkey) -> Result<()> {
+        check_version(ctx.accounts.subscription_config.version)?;
+        ctx.accounts.subscription_config.record_admin_action()?;
+        require_keys_neq!(new_token_treasury, Pubkey::default(), ErrorCode::InvalidTreasury);
+
+        let config = &mut ctx.accounts.subscription_config;
+        let old_token_treasury = config.token_treasury;
+        config.token_treasury = new_token_treasury;
+
+        msg!("Token treasury updated: {} -> {}", old_token_treasury, new_token_treasury);
+        Ok(())
+    }
+
+    /// Redirect future SOL payments to `new_treasury` (admin only).
+    /// Refunds and withdrawals still come from the treasury PDA; SPL payments
+    /// follow `token_treasury` (set_token_treasury).
+    pub fn set_treasury(ctx: Context<AdminConfig>, new_treasury: Pubkey) -> Result<()> {
+        check_version(ctx.accounts.subscription_config.version)?;
+        ctx.accounts.subscription_config.record_admin_action()?;
+        require_keys_neq!(new_treasury, Pubkey::default(), ErrorCode::InvalidTreasury);
+
+        let config = &mut ctx.accounts.subscription_config;
+        let old_treasury = config.treasury;
+        config.treasury = new_treasury;
+
+        emit!(TreasuryUpdated {
+            old_treasury,
+            new_treasury,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        msg!("Treasury updated: {} -> {}", old_treasury, new_treasury);
+        Ok(())
+    }
+
+    /// Admin: Move everything above rent from the treasury PDA to `new_treasury`
+    /// and redirect future payments there, in one step.
+    pub fn sweep_treasury(ctx: Context<SweepTreasury>) -> Result<()> {
+        check_version(ctx.accounts.subscription_config.version)?;
+        ctx.accounts.subscription_config.record_admin_action()?;
+        require!(!ctx.accounts.subscription_config.paused, ErrorCode::SubscriptionsPaused);
+        let treasury = &ctx.accounts.treasury;
+        let new_treasury = ctx.accounts.new_treasury.key();
+        require_keys_neq!(new_treasury, Pubkey::default(), ErrorCode::InvalidTreasury);
+        require_keys_neq!(new_treasury, treasury.key(), ErrorCode::InvalidTreasury);
+
+        let rent_floor = Rent::get()?.minimum_balance(treasury.data_len());
+        let amount = treasury.lamports().saturating_sub(rent_floor);
+        if amount > 0 {
+            let seeds: &[&[u8]] = &[b"treasury".as_ref(), &[ctx.accounts.subscription_config.treasury_bump]];
+            let ix = anchor_lang::solana_program::system_instruction::transfer(
+                &treasury.key(),
+                &new_treasury,
+                amount,
+            );
+            anchor_lang::solana_program::program::invoke_signed(
+                &ix,
+                &[
+                    treasury.to_account_info(),
+                    ctx.accounts.new_treasury.to_account_info(),
+                ],
+                &[seeds],
+            )?;
+        }
+
+        let config = &mut ctx.accounts.subscription_config;
+        let old_treasury = config.treasury;
+        config.treasury = new_treasury;
+
+        emit!(TreasurySwept {
+            old_treasury,
+            new_treasury,
+            amount,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        msg!("Treasury swept: amount={} {} -> {}", amount, old_treasury, new_treasury);
+        Ok(())
+    }
+
+    /// Admin: Propose a new admin. Takes effect once they call `accept_admin`.
+    pub fn propose_admin(ctx: Context<AdminConfig>, new_admin: Pubkey) -> Result<()> {
+        check_version(ctx.accounts.subscription_config.version)?;
+        ctx.accounts.subscription_config.record_admin_action()?;
+        let config = &mut ctx.accounts.subscription_config;
+        config.pending_admin = Some(new_admin);
+
+        msg!("Admin transfer proposed: current={} pending={}", config.admin, new_admin);
+        Ok(())
+    }
+
+    /// Pending admin: Accept the admin role proposed by the current admin.
+    pub fn accept_admin(ctx: Context<AcceptAdmin>) -> Result<()> {
+        check_version(ctx.accounts.subscription_config.version)?;
+        ctx.accounts.subscription_config.record_admin_action()?;
+        let config = &mut ctx.accounts.subscription_config;
+        let old_admin = config.admin;
+        config.admin = ctx.accounts.new_admin.key();
+        config.pending_admin = None;
+
+        msg!("Admin transfer accepted: old={} new={}", old_admin, config.admin);
+        Ok(())
+    }
+
+    /// Admin: Cancel a pending admin transfer.
+    pub fn cancel_admin_transfer(ctx: Context<AdminConfig>) -> Result<()> {
+        check_version(ctx.accounts.subscription_config.version)?;
+        ctx.accounts.subscription_config.record_admin_action()?;
+        let config = &mut ctx.accounts.subscription_config;
+        require!(config.pending_admin.is_some(), ErrorCode::NoPendingAdmin);
+        config.pending_admin = None;
+
+        msg!("Admin transfer cancelled");
+        Ok(())
+    }
+}
+
+// ============================================================================
+// Helpers
+// ============================================================================
+
+/// Validates the user-supplied fields of a safety report.
+fn validate_report_fields(
+    protocol_name: &str,
+    risk_score: u8,
+    flags: u32,
+    report_uri: &str,
+    category: u8,
+) -> Result<()> {
+    require!(risk_score <= 100, ErrorCode::InvalidRiskScore);
+    require!(flags & !VALID_FLAGS_MASK == 0, ErrorCode::InvalidFlags);
+    require!(category <= CATEGORY_BRIDGE, ErrorCode::InvalidCategory);
+    require!(protocol_name.len() <= MAX_PROTOCOL_NAME_LEN, ErrorCode::ProtocolNameTooLong);
+    require!(report_uri.len() <= 200, ErrorCode::ReportUriTooLong);
+    Ok(())
+}
+
+/// Tier prices must be at least MIN_TIER_PRICE and non-decreasing by tier.
+fn validate_pricing(prices: &[u64]) -> Result<()> {
+    require!(!prices.is_empty() && prices.len() <= MAX_TIERS, ErrorCode::InvalidTierCount);
+    require!(prices[0] >= MIN_TIER_PRICE, ErrorCode::PriceBelowFloor);
+    require!(prices.windows(2).all(|w| w[0] <= w[1]), ErrorCode::PricingOrderInvalid);
+    Ok(())
+}
+
+/// Rejects accounts written by an older layout; they must be migrated first.
+fn check_version(version: u8) -> Result<()> {
+    require!(version >= CURRENT_VERSION, ErrorCode::UnsupportedAccountVersion);
+    Ok(())
+}
+
+/// Decodes a SafetyReport account (discriminator included) stored by any
+/// supported layout into the current one. An account of the v0.2.0 size
+/// predates `version` and is read as LegacySafetyReport; any other starts with
+/// the version 1 fields and is followed by those each later version appended
+/// (see CURRENT_VERSION). Fields its version lacks keep their defaults.
+fn decode_old_report(data: &[u8]) -> Result<SafetyReport> {
+    require!(data.len() > 8 && data[..8] == SafetyReport::DISCRIMINATOR, ErrorCode::InvalidReportAccount);
+    let body = &mut &data[8..];
+    let base = if data.len() == 8 + LegacySafetyReport::INIT_SPACE {
+        read_field::<LegacySafetyReport>(body)?.into_v1()
+    } else {
+        read_field::<SafetyReportV1>(body)?
+    };
+    require!(base.version <= CURRENT_VERSION, ErrorCode::UnsupportedAccountVersion);
+    let version = base.version;
+    let mut report = base.upgrade();
+    if version >= 2 {
+        report.resolved = read_field(body)?;
+    }
+    if version >= 5 {
+        report.active = read_field(body)?;
+    }
+    if version >= 8 {
+        report.category = read_field(body)?;
+    }
+    if version >= 17 {
+        report.finalized = read_field(body)?;
+    }
+    if version >= 19 {
+        report.report_slot = read_field(body)?;
+    }
+    if version >= 22 {
+        report.cosigner = read_field(body)?;
+    }
+    Ok(report)
+}
+
+/// Decodes a Registry account stored by any supported layout into the current
+/// one, like decode_old_report: v0.2.0 is recognised by its size, any other
+/// layout starts with the version 1 fields.
+fn decode_old_registry(data: &[u8]) -> Result<Registry> {
+    require!(data.len() > 8 && data[..8] == Registry::DISCRIMINATOR, ErrorCode::UnsupportedAccountLayout);
+    let body = &mut &data[8..];
+    let base = if data.len() == 8 + LegacyRegistry::INIT_SPACE {
+        read_field::<LegacyRegistry>(body)?.into_v1()
+    } else {
+        read_field::<RegistryV1>(body)?
+    };
+    require!(base.version <= CURRENT_VERSION, ErrorCode::UnsupportedAccountVersion);
+    let version = base.version;
+    let mut registry = base.upgrade();
+    if version >= 4 {
+        registry.min_update_interval = read_field(body)?;
+    }
+    if version >= 6 {
+        registry.signers = read_field(body)?;
+        registry.threshold = read_field(body)?;
+    }
+    if version >= 11 {
+        registry.flag_penalty_threshold = read_field(body)?;
+        registry.max_score_with_flags = read_field(body)?;
+    }
+    if version >= 24 {
+        registry.safe_score_min = read_field(body)?;
+        registry.safe_level_min = read_field(body)?;
+    }
+    Ok(registry)
+}
+
+/// Decodes a SubscriptionConfig account stored by any supported layout into
+/// the current one. Versions 1 and 2 held the basic/pro/alpha fields that
+/// version 3 replaced with per-tier vectors; `treasury_bump` fills in the
+/// treasury PDA bump, which v0.2.0 did not store.
+fn decode_old_config(data: &[u8], treasury_bump: u8) -> Result<SubscriptionConfig> {
+    require!(
+        data.len() > 8 && data[..8] == SubscriptionConfig::DISCRIMINATOR,
+        ErrorCode::UnsupportedAccountLayout
+    );
+    let body = &mut &data[8..];
+    let base = if data.len() == 8 + LegacySubscriptionConfig::INIT_SPACE {
+        read_field::<LegacySubscriptionConfig>(body)?.into_v1(treasury_bump).into_v3()
+    } else if data[8] < 3 {
+        read_field::<SubscriptionConfigV1>(body)?.into_v3()
+    } else {
+        read_field::<SubscriptionConfigV3>(body)?
+    };
+    require!(base.version <= CURRENT_VERSION, ErrorCode::UnsupportedAccountVersion);
+    let version = base.version;
+    let mut config = base.upgrade();
+    if version >= 7 {
+        config.refund_window = read_field(body)?;
+        config.refund_mode = read_field(body)?;
+    }
+    if version >= 9 {
+        config.max_subscribers = read_field(body)?;
+    }
+    if version >= 10 {
+        config.promo_prices = read_field(body)?;
+        config.promo_ends_at = read_field(body)?;
+    }
+    if version >= 12 {
+        config.report_quotas = read_field(body)?;
+        config.report_quota_period = read_field(body)?;
+    }
+    if version >= 13 {
+        config.whitelist_root = read_field(body)?;
+    }
+    if version >= 14 {
+        config.annual_discount_bps = read_field(body)?;
+    }
+    if version >= 16 {
+        config.last_admin_action = read_field(body)?;
+        config.inactivity_timeout = read_field(body)?;
+    }
+    if version >= 18 {
+        config.token_treasury = read_field(body)?;
+    }
+    if version >= 21 {
+        config.late_penalty_bps = read_field(body)?;
+    }
+    if version >= 23 {
+        config.subscribe_cooldown = read_field(body)?;
+    }
+    if version >= 25 {
+        config.revenue_split_set = read_field(body)?;
+    }
+    Ok(config)
+}
+
+/// Decodes a Subscription account stored by any supported layout into the
+/// current one, like decode_old_report.
+fn decode_old_subscription(data: &[u8]) -> Result<Subscription> {
+    require!(data.len() > 8 && data[..8] == Subscription::DISCRIMINATOR, ErrorCode::UnsupportedAccountLayout);
+    let body = &mut &data[8..];
+    let base = if data.len() == 8 + LegacySubscription::INIT_SPACE {
+        read_field::<LegacySubscription>(body)?.into_v1()
+    } else {
+        read_field::<SubscriptionV1>(body)?
+    };
+    require!(base.version <= CURRENT_VERSION, ErrorCode::UnsupportedAccountVersion);
+    let version = base.version;
+    let mut subscription = base.upgrade();
+    if version >= 15 {
+        subscription.receipt_mint = read_field(body)?;
+    }
+    if version >= 20 {
+        subscription.first_subscribed_at = read_field(body)?;
+        subscription.total_active_seconds = read_field(body)?;
+        subscription.active_since = read_field(body)?;
+    }
+    Ok(subscription)
+}
+
+/// Borsh-decodes the next field of an account body being migrated.
+fn read_field<T: AnchorDeserialize>(body: &mut &[u8]) -> Result<T> {
+    T::deserialize(body).map_err(|_| error!(ErrorCode::UnsupportedAccountLayout))
+}
+
+/// Grows a migrated account to `space`, with `payer` covering the extra rent,
+/// and writes `value` over it. Returns the old size.
+fn rewrite_migrated<'info, T: AccountSerialize>(
+    info: &AccountInfo<'info>,
+    payer: &AccountInfo<'info>,
+    space: usize,
+    value: &T,
+) -> Result<usize> {
+    let shortfall = Rent::get()?.minimum_balance(space).saturating_sub(info.lamports());
+    if shortfall > 0 {
+        let ix = anchor_lang::solana_program::system_instruction::transfer(payer.key, info.key, shortfall);
+        anchor_lang::solana_program::program::invoke(&ix, &[payer.clone(), info.clone()])?;
+    }
+    let old_size = info.data_len();
+    info.realloc(space, false)?;
+    let mut data = info.try_borrow_mut_data()?;
+    value.try_serialize(&mut &mut data[..])?;
+    Ok(old_size)
+}
+
+/// Coupon PDA seed for a promo code: `keccak(code)`, the `code_hash` given to create_coupon.
+fn coupon_seed(code: &Option<String>) -> [u8; 32] {
+    anchor_lang::solana_program::keccak::hash(code.as_deref().unwrap_or_default().as_bytes()).to_bytes()
+}
+
+/// Trailing PDA seed for report slot `slot`: empty for slot 0, so default
+/// reports keep the original `[safety_report, token_mint, authority]` address.
+fn report_slot_seed(slot: &u8) -> &[u8] {
+    if *slot == 0 {
+        &[]
+    } else {
+        std::slice::from_ref(slot)
+    }
+}
+
+/// Validates a report submitted as a ReportInput (batch and multisig submissions).
+fn validate_report_input(input: &ReportInput) -> Result<()> {
+    validate_report_fields(
+        &input.protocol_name,
+        input.risk_score,
+        input.flags,
+        &input.report_uri,
+        input.category,
+    )?;
+    require!(input.min_tier as usize <= MAX_TIERS, ErrorCode::InvalidTier);
+    Ok(())
+}
+
+fn validate_durations(durations: &[i64]) -> Result<()> {
+    require!(!durations.is_empty() && durations.len() <= MAX_TIERS, ErrorCode::InvalidTierCount);
+    require!(durations.iter().all(|&d| d > 0), ErrorCode::InvalidDuration);
+    Ok(())
+}
+
+/// Adds `token_mint` to the FlagIndex of every bit newly set in `new_flags` and
+/// removes it from every bit cleared since `old_flags`. `accounts` must hold the
+/// FlagIndex of each changed bit, in ascending bit order.
+fn update_flag_indexes<'info>(
+    accounts: &'info [AccountInfo<'info>],
+    token_mint: Pubkey,
+    old_flags: u32,
+    new_flags: u32,
+) -> Result<()> {
+    let changed = old_flags ^ new_flags;
+    require!(
+        accounts.len() == changed.count_ones() as usize,
+        ErrorCode::InvalidRemainingAccounts
+    );
+
+    let mut accounts = accounts.iter();
+    for bit in 0..32u8 {
+        let mask = 1u32 << bit;
+        if changed & mask == 0 {
+            continue;
+        }
+        let info = accounts.next().ok_or(ErrorCode::InvalidRemainingAccounts)?;
+        let mut index: Account<FlagIndex> = Account::try_from(info)?;
+        require!(index.flag_bit == bit, ErrorCode::FlagIndexMismatch);
+
+        if new_flags & mask != 0 {
+            index.insert(token_mint);
+        } else {
+            index.remove(&token_mint);
+        }
+        index.exit(&crate::ID)?;
+    }
+    Ok(())
+}
+
+/// Pro-rates `amount` by `elapsed / period`, rounding down.
+/// Returns 0 for non-positive inputs.
+fn prorated_amount(amount: u64, elapsed: i64, period: i64) -> u64 {
+    if elapsed <= 0 || period <= 0 {
+        return 0;
+    }
+    let scaled = (amount as u128) * (elapsed as u128) / (period as u128);
+    scaled.min(u64::MAX as u128) as u64
+}
+
+/// Returns `bps` basis points of `amount`, rounding down. `bps` must be <= 10_000.
+fn bps_of(amount: u64, bps: u16) -> u64 {
+    ((amount as u128) * (bps as u128) / 10_000) as u64
+}
+
+/// Pays `amount` lamports of revenue from `payer`: to `treasury`, or across the
+/// `split` recipients when set. `split_set` is the config's `revenue_split_set`;
+/// once it is, the split must be passed. `recipients` must hold each entry's
+/// wallet, in entry order; the last one also receives the rounding remainder.
+/// Every payment is checked against the recipient's balance (see `transfer_sol_verified`).
+fn pay_revenue<'info>(
+    payer: &AccountInfo<'info>,
+    treasury: &AccountInfo<'info>,
+    split_set: bool,
+    split: Option<&RevenueSplit>,
+    recipients: &[AccountInfo<'info>],
+    amount: u64,
+) -> Result<()> {
+    let Some(split) = split else {
+        require!(!split_set, ErrorCode::RevenueSplitRequired);
+        return transfer_sol_verified(payer, treasury, amount);
+    };
+    require!(
+        recipients.len() == split.entries.len(),
+        ErrorCode::InvalidRemainingAccounts
+    );
+
+    let mut remaining = amount;
+    for (i, (entry, info)) in split.entries.iter().zip(recipients).enumerate() {
+        require_keys_eq!(info.key(), entry.recipient, ErrorCode::InvalidSplitRecipient);
+        let share = if i + 1 == split.entries.len() {
+            remaining
+        } else {
+            bps_of(amount, entry.bps)
+        };
+        transfer_sol_verified(payer, info, share)?;
+        remaining = remaining.checked_sub(share).ok_or(ErrorCode::ArithmeticOverflow)?;
+    }
+    Ok(())
+}
+
+/// Fails unless the treasury can pay out `amount` lamports and stay rent-exempt.
+fn assert_treasury_solvent(treasury: &AccountInfo, amount: u64) -> Result<()> {
+    let rent_floor = Rent::get()?.minimum_balance(treasury.data_len());
+    let available = treasury.lamports().saturating_sub(rent_floor);
+    require!(amount <= available, ErrorCode::TreasuryUnderRent);
+    Ok(())
+}
+
+/// Counts a batch-submitted report in its mint's TokenConsensus, creating the
+/// account (paid by `payer`) if it doesn't exist yet.
+fn add_to_consensus<'info>(
+    info: &'info AccountInfo<'info>,
+    payer: &AccountInfo<'info>,
+    program_id: &Pubkey,
+    token_mint: Pubkey,
+    bump: u8,
+    risk_score: u8,
+    risk_level: RiskLevel,
+) -> Result<()> {
+    if info.lamports() == 0 {
+        let space = 8 + TokenConsensus::INIT_SPACE;
+        let ix = anchor_lang::solana_program::system_instruction::create_account(
+            payer.key,
+            info.key,
+            Rent::get()?.minimum_balance(space),
+            space as u64,
+            program_id,
+        );
+        anchor_lang::solana_program::program::invoke_signed(
+            &ix,
+            &[payer.clone(), info.clone()],
+            &[&[b"consensus", token_mint.as_ref(), &[bump]]],
+        )?;
+
+        let mut consensus = TokenConsensus::new(token_mint, bump);
+        consensus.add(risk_score, risk_level)?;
+        let mut data = info.try_borrow_mut_data()?;
+        consensus.try_serialize(&mut &mut data[..])?;
+    } else {
+        let mut consensus: Account<TokenConsensus> = Account::try_from(info)?;
+        consensus.add(risk_score, risk_level)?;
+        consensus.exit(program_id)?;
+    }
+    Ok(())
+}
+
+/// Lists `authority`'s slot-0 report in the TokenReportIndex at `info` (a
+/// submit_reports_batch remaining account), creating the index if needed.
+fn add_to_token_index<'info>(
+    info: &'info AccountInfo<'info>,
+    payer: &AccountInfo<'info>,
+    program_id: &Pubkey,
+    token_mint: Pubkey,
+    bump: u8,
+    authority: Pubkey,
+) -> Result<()> {
+    if info.lamports() == 0 {
+        let space = 8 + TokenReportIndex::INIT_SPACE;
+        let ix = anchor_lang::solana_program::system_instruction::create_account(
+            payer.key,
+            info.key,
+            Rent::get()?.minimum_balance(space),
+            space as u64,
+            program_id,
+        );
+        anchor_lang::solana_program::program::invoke_signed(
+            &ix,
+            &[payer.clone(), info.clone()],
+            &[&[b"token_index", token_mint.as_ref(), &[bump]]],
+        )?;
+
+        let mut index = TokenReportIndex::new(token_mint, bump);
+        index.insert(authority, 0);
+        let mut data = info.try_borrow_mut_data()?;
+        index.try_serialize(&mut &mut data[..])?;
+    } else {
+        let mut index: Account<TokenReportIndex> = Account::try_from(info)?;
+        index.insert(authority, 0);
+        index.exit(program_id)?;
+    }
+    Ok(())
+}
+
+/// Starts or extends a free subscription at `tier` for `user`. An active paid
+/// subscription is extended by the tier duration and switched to that tier;
+/// otherwise the new cycle starts at `now`.
+fn grant_free_subscription(
+    subscription: &mut Account<Subscription>,
+    config: &mut SubscriptionConfig,
+    user: Pubkey,
+    bump: u8,
+    tier: u8,
+    now: i64,
+) -> Result<()> {
+    let is_new = subscription.user == Pubkey::default();
+    if !is_new {
+        check_version(subscription.version)?;
+    }
+    let was_counted = !is_new && !subscription.is_trial && subscription.tier != 0;
+    let base_time = if was_counted && subscription.expires_at > now {
+        subscription.expires_at
+    } else {
+        now
+    };
+
+    if is_new {
+        subscription.user = user;
+        subscription.created_at = now;
+        subscription.total_paid = 0;
+        subscription.auto_renew = false;
+        subscription.version = CURRENT_VERSION;
+        subscription.bump = bump;
+    }
+    subscription.tier = tier;
+    subscription.accrue_active_time(now)?;
+    subscription.expires_at = base_time.checked_add(config.tier_duration(tier)?).ok_or(ErrorCode::ArithmeticOverflow)?;
+    subscription.is_trial = false;
+    subscription.disabled = false;
+    subscription.pending_tier = 0;
+    subscription.pending_tier_at = 0;
+
+    if !was_counted {
+        config.add_subscriber(tier)?;
+    }
+    Ok(())
+}
+
+/// True if `proof` links `leaf` to `root`, hashing each pair in sorted order.
+fn verify_merkle_proof(proof: &[[u8; 32]], root: [u8; 32], leaf: [u8; 32]) -> bool {
+    let computed = proof.iter().fold(leaf, |node, sibling| {
+        let (lo, hi) = if node <= *sibling { (node, *sibling) } else { (*sibling, node) };
+        anchor_lang::solana_program::keccak::hashv(&[&lo, &hi]).to_bytes()
+    });
+    computed == root
+}
+
+/// Moves lamports out of an account owned by this program by adjusting
+/// balances directly (the system program can't debit it).
+fn move_lamports(from: &AccountInfo, to: &AccountInfo, amount: u64) -> Result<()> {
+    let from_balance = from.lamports().checked_sub(amount).ok_or(ErrorCode::ArithmeticOverflow)?;
+    let to_balance = to.lamports().checked_add(amount).ok_or(ErrorCode::ArithmeticOverflow)?;
+    **from.try_borrow_mut_lamports()? = from_balance;
+    **to.try_borrow_mut_lamports()? = to_balance;
+    Ok(())
+}
+
+/// Transfers lamports from a signer to `to` via the system program. No-op for 0.
+fn transfer_sol<'info>(from: &AccountInfo<'info>, to: &AccountInfo<'info>, amount: u64) -> Result<()> {
+    if amount == 0 {
+        return Ok(());
+    }
+    let ix = anchor_lang::solana_program::system_instruction::transfer(from.key, to.key, amount);
+    anchor_lang::solana_program::program::invoke(&ix, &[from.clone(), to.clone()])?;
+    Ok(())
+}
+
+/// transfer_sol, then fails with PaymentNotReceived unless `to`'s balance grew by
+/// exactly `amount`, so a payment that credits some other account can't slip
+/// through and still leave the purchase recorded.
+fn transfer_sol_verified<'info>(from: &AccountInfo<'info>, to: &AccountInfo<'info>, amount: u64) -> Result<()> {
+    let before = to.lamports();
+    transfer_sol(from, to, amount)?;
+    let received = to.lamports().checked_sub(before).ok_or(ErrorCode::PaymentNotReceived)?;
+    require!(received == amount, ErrorCode::PaymentNotReceived);
+    Ok(())
+}
+
+// ============================================================================
+// Account Contexts - Registry
+// ============================================================================
+
+#[derive(Accounts)]
+pub struct InitializeRegistry<'info> {
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + Registry::INIT_SPACE,
+        seeds = [b"registry", authority.key().as_ref()],
+        bump
+    )]
+    pub registry: Account<'info, Registry>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(
+    protocol_name: String,
+    risk_score: u8,
+    risk_level: RiskLevel,
+    flags: u32,
+    report_uri: String,
+    min_tier: u8,
+    category: u8,
+    report_slot: u8
+)]
+pub struct SubmitReport<'info> {
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + SafetyReport::INIT_SPACE,
+        seeds = [b"safety_report", token_mint.key().as_ref(), authority.key().as_ref(), report_slot_seed(&report_slot)],
+        bump
+    )]
+    pub safety_report: Account<'info, SafetyReport>,
+
+    #[account(
+        mut,
+        seeds = [b"registry", authority.key().as_ref()],
+        bump = registry.bump,
+        has_one = authority
+    )]
+    pub registry: Account<'info, Registry>,
+
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = 8 + Reporter::INIT_SPACE,
+        seeds = [b"reporter", authority.key().as_ref()],
+        bump
+    )]
+    pub reporter: Account<'info, Reporter>,
+
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = 8 + GlobalStats::INIT_SPACE,
+        seeds = [b"global_stats"],
+        bump
+    )]
+    pub global_stats: Account<'info, GlobalStats>,
+
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = 8 + TokenConsensus::INIT_SPACE,
+        seeds = [b"consensus", token_mint.key().as_ref()],
+        bump
+    )]
+    pub consensus: Account<'info, TokenConsensus>,
+
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = 8 + TokenReportIndex::INIT_SPACE,
+        seeds = [b"token_index", token_mint.key().as_ref()],
+        bump
+    )]
+    pub token_index: Account<'info, TokenReportIndex>,
+
+    #[account(
+        seeds = [b"subscription_config"],
+        bump = subscription_config.bump
+    )]
+    pub subscription_config: Account<'info, SubscriptionConfig>,
+
+    /// The reporting authority's subscription, if any; sets its report quota.
+    #[account(
+        seeds = [b"subscription", authority.key().as_ref()],
+        bump = subscription.bump
+    )]
+    pub subscription: Option<Account<'info, Subscription>>,
+
+    /// CHECK: Token mint address used as seed.
+    pub token_mint: AccountInfo<'info>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// A registry signer co-signing the report; required for RiskLevel::High.
+    pub cosigner: Option<Signer<'info>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateReport<'info> {
+    #[account(
+        mut,
+        seeds = [b"safety_report", safety_report.token_mint.as_ref(), authority.key().as_ref(), safety_report.slot_seed()],
+        bump = safety_report.bump,
+        has_one = authority,
+        constraint = !safety_report.finalized @ ErrorCode::ReportFinalized
+    )]
+    pub safety_report: Account<'info, SafetyReport>,
+
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = 8 + ReportHistory::INIT_SPACE,
+        seeds = [b"report_history", safety_report.token_mint.as_ref(), authority.key().as_ref(), safety_report.slot_seed()],
+        bump
+    )]
+    pub report_history: Account<'info, ReportHistory>,
+
+    #[account(
+        seeds = [b"registry", authority.key().as_ref()],
+        bump = registry.bump,
+        has_one = authority
+    )]
+    pub registry: Account<'info, Registry>,
+
+    #[account(
+        mut,
+        seeds = [b"global_stats"],
+        bump = global_stats.bump
+    )]
+    pub global_stats: Account<'info, GlobalStats>,
+
+    #[account(
+        mut,
+        seeds = [b"consensus", safety_report.token_mint.as_ref()],
+        bump = consensus.bump
+    )]
+    pub consensus: Account<'info, TokenConsensus>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// A registry signer co-signing the new values; required for RiskLevel::High.
+    pub cosigner: Option<Signer<'info>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateRiskScore<'info> {
+    #[account(
+        mut,
+        seeds = [b"safety_report", safety_report.token_mint.as_ref(), authority.key().as_ref(), safety_report.slot_seed()],
+        bump = safety_report.bump,
+        has_one = authority,
+        constraint = !safety_report.finalized @ ErrorCode::ReportFinalized
+    )]
+    pub safety_report: Account<'info, SafetyReport>,
+
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = 8 + ReportHistory::INIT_SPACE,
+        seeds = [b"report_history", safety_report.token_mint.as_ref(), authority.key().as_ref(), safety_report.slot_seed()],
+        bump
+    )]
+    pub report_history: Account<'info, ReportHistory>,
+
+    #[account(
+        seeds = [b"registry", authority.key().as_ref()],
+        bump = registry.bump,
+        has_one = authority
+    )]
+    pub registry: Account<'info, Registry>,
+
+    #[account(
+        mut,
+        seeds = [b"global_stats"],
+        bump = global_stats.bump
+    )]
+    pub global_stats: Account<'info, GlobalStats>,
+
+    #[account(
+        mut,
+        seeds = [b"consensus", safety_report.token_mint.as_ref()],
+        bump = consensus.bump
+    )]
+    pub consensus: Account<'info, TokenConsensus>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Every account here is `init_if_needed`, so the first upsert for a mint pays
+/// rent for the report and its `ReportHistory` (and the `Reporter`,
+/// `GlobalStats` and `TokenConsensus` if missing) even though the history is
+/// only written by later upserts.
+#[derive(Accounts)]
+#[instruction(input: ReportInput)]
+pub struct UpsertReport<'info> {
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = 8 + SafetyReport::INIT_SPACE,
+        seeds = [b"safety_report", input.token_mint.as_ref(), authority.key().as_ref()],
+        bump
+    )]
+    pub safety_report: Account<'info, SafetyReport>,
+
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = 8 + ReportHistory::INIT_SPACE,
+        seeds = [b"report_history", input.token_mint.as_ref(), authority.key().as_ref()],
+        bump
+    )]
+    pub report_history: Account<'info, ReportHistory>,
+
+    #[account(
+        mut,
+        seeds = [b"registry", authority.key().as_ref()],
+        bump = registry.bump,
+        has_one = authority
+    )]
+    pub registry: Account<'info, Registry>,
+
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = 8 + Reporter::INIT_SPACE,
+        seeds = [b"reporter", authority.key().as_ref()],
+        bump
+    )]
+    pub reporter: Account<'info, Reporter>,
+
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = 8 + GlobalStats::INIT_SPACE,
+        seeds = [b"global_stats"],
+        bump
+    )]
+    pub global_stats: Account<'info, GlobalStats>,
+
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = 8 + TokenConsensus::INIT_SPACE,
+        seeds = [b"consensus", input.token_mint.as_ref()],
+        bump
+    )]
+    pub consensus: Account<'info, TokenConsensus>,
+
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = 8 + TokenReportIndex::INIT_SPACE,
+        seeds = [b"token_index", input.token_mint.as_ref()],
+        bump
+    )]
+    pub token_index: Account<'info, TokenReportIndex>,
+
+    #[account(
+        seeds = [b"subscription_config"],
+        bump = subscription_config.bump
+    )]
+    pub subscription_config: Account<'info, SubscriptionConfig>,
+
+    /// The reporting authority's subscription, if any; sets its report quota.
+    #[account(
+        seeds = [b"subscription", authority.key().as_ref()],
+        bump = subscription.bump
+    )]
+    pub subscription: Option<Account<'info, Subscription>>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// A registry signer co-signing the new values; required for RiskLevel::High.
+    pub cosigner: Option<Signer<'info>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SubmitReportsBatch<'info> {
+    #[account(
+        mut,
+        seeds = [b"registry", authority.key().as_ref()],
+        bump = registry.bump,
+        has_one = authority
+    )]
+    pub registry: Account<'info, Registry>,
+
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = 8 + Reporter::INIT_SPACE,
+        seeds = [b"reporter", authority.key().as_ref()],
+        bump
+    )]
+    pub reporter: Account<'info, Reporter>,
+
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = 8 + GlobalStats::INIT_SPACE,
+        seeds = [b"global_stats"],
+        bump
+    )]
+    pub global_stats: Account<'info, GlobalStats>,
+
+    #[account(
+        seeds = [b"subscription_config"],
+        bump = subscription_config.bump
+    )]
+    pub subscription_config: Account<'info, SubscriptionConfig>,
+
+    /// The reporting authority's subscription, if any; sets its report quota.
+    #[account(
+        seeds = [b"subscription", authority.key().as_ref()],
+        bump = subscription.bump
+    )]
+    pub subscription: Option<Account<'info, Subscription>>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// A registry signer co-signing every report in the batch; required if any is RiskLevel::High.
+    pub cosigner: Option<Signer<'info>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CloseReport<'info> {
+    #[account(
+        mut,
+        close = authority,
+        seeds = [b"safety_report", safety_report.token_mint.as_ref(), authority.key().as_ref(), safety_report.slot_seed()],
+        bump = safety_report.bump,
+        has_one = authority,
+        constraint = !safety_report.finalized @ ErrorCode::ReportFinalized
+    )]
+    pub safety_report: Account<'info, SafetyReport>,
+
+    #[account(
+        mut,
+        seeds = [b"reporter", authority.key().as_ref()],
+        bump = reporter.bump,
+        has_one = authority
+    )]
+    pub reporter: Account<'info, Reporter>,
+
+    #[account(
+        mut,
+        seeds = [b"global_stats"],
+        bump = global_stats.bump
+    )]
+    pub global_stats: Account<'info, GlobalStats>,
+
+    #[account(
+        mut,
+        seeds = [b"consensus", safety_report.token_mint.as_ref()],
+        bump = consensus.bump
+    )]
+    pub consensus: Account<'info, TokenConsensus>,
+
+    /// The mint's TokenReportIndex; the report's slot is removed from it.
+    #[account(
+        mut,
+        seeds = [b"token_index", safety_report.token_mint.as_ref()],
+        bump = token_index.bump
+    )]
+    pub token_index: Account<'info, TokenReportIndex>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(input: ReportInput)]
+pub struct SubmitReportMultisig<'info> {
+    #[account(
+        seeds = [b"registry", registry.authority.as_ref()],
+        bump = registry.bump
+    )]
+    pub registry: Account<'info, Registry>,
+
+    /// Fails to init while a proposal for this mint is already pending.
+    #[account(
+        init,
+        payer = signer,
+        space = 8 + PendingReport::INIT_SPACE,
+        seeds = [b"pending_report", registry.key().as_ref(), input.token_mint.as_ref()],
+        bump
+    )]
+    pub pending_report: Account<'info, PendingReport>,
+
+    #[account(mut)]
+    pub signer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ApproveReportMultisig<'info> {
+    #[account(
+        seeds = [b"registry", registry.authority.as_ref()],
+        bump = registry.bump
+    )]
+    pub registry: Account<'info, Registry>,
+
+    #[account(
+        mut,
+        seeds = [b"pending_report", registry.key().as_ref(), pending_report.input.token_mint.as_ref()],
+        bump = pending_report.bump,
+        has_one = registry
+    )]
+    pub pending_report: Account<'info, PendingReport>,
+
+    pub signer: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ExecuteReportMultisig<'info> {
+    #[account(
+        mut,
+        seeds = [b"registry", registry.authority.as_ref()],
+        bump = registry.bump
+    )]
+    pub registry: Account<'info, Registry>,
+
+    #[account(
+        mut,
+        close = proposer,
+        seeds = [b"pending_report", registry.key().as_ref(), pending_report.input.token_mint.as_ref()],
+        bump = pending_report.bump,
+        has_one = registry,
+        has_one = proposer
+    )]
+    pub pending_report: Account<'info, PendingReport>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + SafetyReport::INIT_SPACE,
+        seeds = [b"safety_report", pending_report.input.token_mint.as_ref(), registry.authority.as_ref()],
+        bump
+    )]
+    pub safety_report: Account<'info, SafetyReport>,
+
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = 8 + Reporter::INIT_SPACE,
+        seeds = [b"reporter", registry.authority.as_ref()],
+        bump
+    )]
+    pub reporter: Account<'info, Reporter>,
+
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = 8 + GlobalStats::INIT_SPACE,
+        seeds = [b"global_stats"],
+        bump
+    )]
+    pub global_stats: Account<'info, GlobalStats>,
+
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = 8 + TokenConsensus::INIT_SPACE,
+        seeds = [b"consensus", pending_report.input.token_mint.as_ref()],
+        bump
+    )]
+    pub consensus: Account<'info, TokenConsensus>,
+
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = 8 + TokenReportIndex::INIT_SPACE,
+        seeds = [b"token_index", pending_report.input.token_mint.as_ref()],
+        bump
+    )]
+    pub token_index: Account<'info, TokenReportIndex>,
+
+    #[account(
+        seeds = [b"subscription_config"],
+        bump = subscription_config.bump
+    )]
+    pub subscription_config: Account<'info, SubscriptionConfig>,
+
+    /// The reporting authority's subscription, if any; sets its report quota.
+    #[account(
+        seeds = [b"subscription", registry.authority.as_ref()],
+        bump = subscription.bump
+    )]
+    pub subscription: Option<Account<'info, Subscription>>,
+
+    /// CHECK: Receives the pending report's rent; checked by has_one.
+    #[account(mut)]
+    pub proposer: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(delegate: Pubkey)]
+pub struct AddDelegate<'info> {
+    #[account(
+        seeds = [b"registry", authority.key().as_ref()],
+        bump = registry.bump,
+        has_one = authority
+    )]
+    pub registry: Account<'info, Registry>,
+
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = 8 + Delegate::INIT_SPACE,
+        seeds = [b"delegate", authority.key().as_ref(), delegate.as_ref()],
+        bump
+    )]
+    pub delegate_account: Account<'info, Delegate>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RevokeDelegate<'info> {
+    #[account(
+        mut,
+        seeds = [b"delegate", authority.key().as_ref(), delegate_account.delegate.as_ref()],
+        bump = delegate_account.bump,
+        has_one = authority
+    )]
+    pub delegate_account: Account<'info, Delegate>,
+
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(input: ReportInput)]
+pub struct SubmitReportDelegated<'info> {
+    #[account(
+        init,
+        payer = delegate,
+        space = 8 + SafetyReport::INIT_SPACE,
+        seeds = [b"safety_report", input.token_mint.as_ref(), registry.authority.as_ref()],
+        bump
+    )]
+    pub safety_report: Account<'info, SafetyReport>,
+
+    #[account(
+        mut,
+        seeds = [b"registry", registry.authority.as_ref()],
+        bump = registry.bump
+    )]
+    pub registry: Account<'info, Registry>,
+
+    #[account(
+        seeds = [b"delegate", registry.authority.as_ref(), delegate.key().as_ref()],
+        bump = delegate_account.bump,
+        constraint = !delegate_account.revoked @ ErrorCode::DelegateRevoked
+    )]
+    pub delegate_account: Account<'info, Delegate>,
+
+    #[account(
+        init_if_needed,
+        payer = delegate,
+        space = 8 + Reporter::INIT_SPACE,
+        seeds = [b"reporter", registry.authority.as_ref()],
+        bump
+    )]
+    pub reporter: Account<'info, Reporter>,
+
+    #[account(
+        init_if_needed,
+        payer = delegate,
+        space = 8 + GlobalStats::INIT_SPACE,
+        seeds = [b"global_stats"],
+        bump
+    )]
+    pub global_stats: Account<'info, GlobalStats>,
+
+    #[account(
+        init_if_needed,
+        payer = delegate,
+        space = 8 + TokenConsensus::INIT_SPACE,
+        seeds = [b"consensus", input.token_mint.as_ref()],
+        bump
+    )]
+    pub consensus: Account<'info, TokenConsensus>,
+
+    #[account(
+        init_if_needed,
+        payer = delegate,
+        space = 8 + TokenReportIndex::INIT_SPACE,
+        seeds = [b"token_index", input.token_mint.as_ref()],
+        bump
+    )]
+    pub token_index: Account<'info, TokenReportIndex>,
+
+    #[account(
+        seeds = [b"subscription_config"],
+        bump = subscription_config.bump
+    )]
+    pub subscription_config: Account<'info, SubscriptionConfig>,
+
+    /// The reporting authority's subscription, if any; sets its report quota.
+    #[account(
+        seeds = [b"subscription", registry.authority.as_ref()],
+        bump = subscription.bump
+    )]
+    pub subscription: Option<Account<'info, Subscription>>,
+
+    #[account(mut)]
+    pub delegate: Signer<'info>,
+
+    /// A registry signer other than the delegate co-signing the report; required for RiskLevel::High.
+    pub cosigner: Option<Signer<'info>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(input: ReportInput)]
+pub struct SubmitReportLite<'info> {
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + SafetyReport::INIT_SPACE,
+        seeds = [b"safety_report", input.token_mint.as_ref(), authority.key().as_ref()],
+        bump
+    )]
+    pub safety_report: Account<'info, SafetyReport>,
+
+    /// Read-only: not locked for writing, unlike in submit_report.
+    #[account(
+        seeds = [b"registry", authority.key().as_ref()],
+        bump = registry.bump,
+        has_one = authority
+    )]
+    pub registry: Account<'info, Registry>,
+
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = 8 + Reporter::INIT_SPACE,
+        seeds = [b"reporter", authority.key().as_ref()],
+        bump
+    )]
+    pub reporter: Account<'info, Reporter>,
+
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = 8 + GlobalStats::INIT_SPACE,
+        seeds = [b"global_stats"],
+        bump
+    )]
+    pub global_stats: Account<'info, GlobalStats>,
+
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = 8 + TokenConsensus::INIT_SPACE,
+        seeds = [b"consensus", input.token_mint.as_ref()],
+        bump
+    )]
+    pub consensus: Account<'info, TokenConsensus>,
+
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = 8 + TokenReportIndex::INIT_SPACE,
+        seeds = [b"token_index", input.token_mint.as_ref()],
+        bump
+    )]
+    pub token_index: Account<'info, TokenReportIndex>,
+
+    #[account(
+        seeds = [b"subscription_config"],
+        bump = subscription_config.bump
+    )]
+    pub subscription_config: Account<'info, SubscriptionConfig>,
+
+    /// The reporting authority's subscription, if any; sets its report quota.
+    #[account(
+        seeds = [b"subscription", authority.key().as_ref()],
+        bump = subscription.bump
+    )]
+    pub subscription: Option<Account<'info, Subscription>>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// A registry signer co-signing the report; required for RiskLevel::High.
+    pub cosigner: Option<Signer<'info>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SyncReportCount<'info> {
+    #[account(
+        mut,
+        seeds = [b"subscription_config"],
+        bump = subscription_config.bump,
+        has_one = admin @ ErrorCode::Unauthorized
+    )]
+    pub subscription_config: Account<'info, SubscriptionConfig>,
+
+    #[account(
+        mut,
+        seeds = [b"registry", registry.authority.as_ref()],
+        bump = registry.bump
+    )]
+    pub registry: Account<'info, Registry>,
+
+    #[account(
+        mut,
+        seeds = [b"reporter", registry.authority.as_ref()],
+        bump = reporter.bump
+    )]
+    pub reporter: Account<'info, Reporter>,
+
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct RecomputeTotalReports<'info> {
+    #[account(
+        mut,
+        seeds = [b"registry", authority.key().as_ref()],
+        bump = registry.bump,
+        has_one = authority
+    )]
+    pub registry: Account<'info, Registry>,
+
+    #[account(
+        mut,
+        seeds = [b"reporter", authority.key().as_ref()],
+        bump = reporter.bump
+    )]
+    pub reporter: Account<'info, Reporter>,
+
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = 8 + ReportRecount::INIT_SPACE,
+        seeds = [b"recount", registry.key().as_ref()],
+        bump
+    )]
+    pub recount: Account<'info, ReportRecount>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetPaused<'info> {
+    #[account(
+        mut,
+        seeds = [b"registry", authority.key().as_ref()],
+        bump = registry.bump,
+        has_one = authority
+    )]
+    pub registry: Account<'info, Registry>,
+
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ProposeRegistryAuthority<'info> {
+    #[account(
+        mut,
+        seeds = [b"registry", authority.key().as_ref()],
+        bump = registry.bump,
+        has_one = authority
+    )]
+    pub registry: Account<'info, Registry>,
+
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct AcceptRegistryAuthority<'info> {
+    /// Rent goes to the new authority, since the old key may be lost.
+    #[account(
+        mut,
+        close = new_authority,
+        seeds = [b"registry", old_registry.authority.as_ref()],
+        bump = old_registry.bump,
+        constraint = old_registry.pending_authority == Some(new_authority.key()) @ ErrorCode::Unauthorized
+    )]
+    pub old_registry: Account<'info, Registry>,
+
+    #[account(
+        init,
+        payer = new_authority,
+        space = 8 + Registry::INIT_SPACE,
+        seeds = [b"registry", new_authority.key().as_ref()],
+        bump
+    )]
+    pub registry: Account<'info, Registry>,
+
+    #[account(mut)]
+    pub new_authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct MigrateReportAuthority<'info> {
+    #[account(
+        mut,
+        close = authority,
+        seeds = [b"safety_report", old_report.token_mint.as_ref(), old_report.authority.as_ref(), old_report.slot_seed()],
+        bump = old_report.bump
+    )]
+    pub old_report: Account<'info, SafetyReport>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + SafetyReport::INIT_SPACE,
+        seeds = [b"safety_report", old_report.token_mint.as_ref(), authority.key().as_ref(), old_report.slot_seed()],
+        bump
+    )]
+    pub safety_report: Account<'info, SafetyReport>,
+
+    /// The old report's history, if it has one; closed once copied to `report_history`.
+    #[account(
+        mut,
+        close = authority,
+        seeds = [b"report_history", old_report.token_mint.as_ref(), old_report.authority.as_ref(), old_report.slot_seed()],
+        bump = old_history.bump
+    )]
+    pub old_history: Option<Account<'info, ReportHistory>>,
+
+    /// Passed exactly when `old_history` is.
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = 8 + ReportHistory::INIT_SPACE,
+        seeds = [b"report_history", old_report.token_mint.as_ref(), authority.key().as_ref(), old_report.slot_seed()],
+        bump
+    )]
+    pub report_history: Option<Account<'info, ReportHistory>>,
+
+    /// The old report's flag reasons, if it has any; closed once copied to `report_flags`.
+    #[account(
+        mut,
+        close = authority,
+        seeds = [b"report_flags", old_report.token_mint.as_ref(), old_report.authority.as_ref(), old_report.slot_seed()],
+        bump = old_flags.bump
+    )]
+    pub old_flags: Option<Account<'info, ReportFlags>>,
+
+    /// Passed exactly when `old_flags` is.
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = 8 + ReportFlags::INIT_SPACE,
+        seeds = [b"report_flags", old_report.token_mint.as_ref(), authority.key().as_ref(), old_report.slot_seed()],
+        bump
+    )]
+    pub report_flags: Option<Account<'info, ReportFlags>>,
+
+    /// The mint's TokenReportIndex; the report's slot moves to the new authority.
+    #[account(
+        mut,
+        seeds = [b"token_index", old_report.token_mint.as_ref()],
+        bump = token_index.bump
+    )]
+    pub token_index: Account<'info, TokenReportIndex>,
+
+    /// The new authority's registry, which must have been handed over from the report's authority.
+    #[account(
+        seeds = [b"registry", authority.key().as_ref()],
+        bump = registry.bump,
+        has_one = authority,
+        constraint = registry.previous_authority == Some(old_report.authority) @ ErrorCode::Unauthorized
+    )]
+    pub registry: Account<'info, Registry>,
+
+    /// The previous authority's profile; the report leaves its active_reports.
+    #[account(
+        mut,
+        seeds = [b"reporter", old_report.authority.as_ref()],
+        bump = old_reporter.bump
+    )]
+    pub old_reporter: Account<'info, Reporter>,
+
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = 8 + Reporter::INIT_SPACE,
+        seeds = [b"reporter", authority.key().as_ref()],
+        bump
+    )]
+    pub reporter: Account<'info, Reporter>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct MigrateReport<'info> {
+    /// CHECK: An older layout does not deserialize as SafetyReport, so
+    /// migrate_report decodes it and checks its PDA and authority itself.
+    #[account(mut, owner = crate::ID)]
+    pub safety_report: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct MigrateRegistry<'info> {
+    /// CHECK: An older layout does not deserialize as Registry, so
+    /// migrate_registry decodes it and checks its authority itself.
+    #[account(
+        mut,
+        owner = crate::ID,
+        seeds = [b"registry", authority.key().as_ref()],
+        bump
+    )]
+    pub registry: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct AdminRegistry<'info> {
+    #[account(
+        mut,
+        seeds = [b"subscription_config"],
+        bump = subscription_config.bump,
+        has_one = admin @ ErrorCode::Unauthorized
+    )]
+    pub subscription_config: Account<'info, SubscriptionConfig>,
+
+    #[account(
+        mut,
+        seeds = [b"registry", registry.authority.as_ref()],
+        bump = registry.bump
+    )]
+    pub registry: Account<'info, Registry>,
+
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(flag_bit: u8)]
+pub struct InitializeFlagIndex<'info> {
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + FlagIndex::INIT_SPACE,
+        seeds = [b"flag_index", [flag_bit].as_ref()],
+        bump
+    )]
+    pub flag_index: Account<'info, FlagIndex>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetReputation<'info> {
+    #[account(
+        mut,
+        seeds = [b"subscription_config"],
+        bump = subscription_config.bump,
+        has_one = admin @ ErrorCode::Unauthorized
+    )]
+    pub subscription_config: Account<'info, SubscriptionConfig>,
+
+    #[account(
+        mut,
+        seeds = [b"reporter", reporter.authority.as_ref()],
+        bump = reporter.bump
+    )]
+    pub reporter: Account<'info, Reporter>,
+
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct AggregateReport<'info> {
+    /// CHECK: Token mint the aggregated reports must refer to.
+    pub token_mint: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CheckReportFresh<'info> {
+    #[account(
+        seeds = [b"safety_report", safety_report.token_mint.as_ref(), safety_report.authority.as_ref(), safety_report.slot_seed()],
+        bump = safety_report.bump
+    )]
+    pub safety_report: Account<'info, SafetyReport>,
+
+    #[account(
+        seeds = [b"registry", safety_report.authority.as_ref()],
+        bump = registry.bump
+    )]
+    pub registry: Account<'info, Registry>,
+}
+
+#[derive(Accounts)]
+pub struct SetReportActive<'info> {
+    #[account(
+        mut,
+        seeds = [b"safety_report", safety_report.token_mint.as_ref(), authority.key().as_ref(), safety_report.slot_seed()],
+        bump = safety_report.bump,
+        has_one = authority,
+        constraint = !safety_report.finalized @ ErrorCode::ReportFinalized
+    )]
+    pub safety_report: Account<'info, SafetyReport>,
+
+    #[account(
+        mut,
+        seeds = [b"global_stats"],
+        bump = global_stats.bump
+    )]
+    pub global_stats: Account<'info, GlobalStats>,
+
+    #[account(
+        mut,
+        seeds = [b"consensus", safety_report.token_mint.as_ref()],
+        bump = consensus.bump
+    )]
+    pub consensus: Account<'info, TokenConsensus>,
+
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct FinalizeReport<'info> {
+    #[account(
+        mut,
+        seeds = [b"safety_report", safety_report.token_mint.as_ref(), authority.key().as_ref(), safety_report.slot_seed()],
+        bump = safety_report.bump,
+        has_one = authority,
+        constraint = !safety_report.finalized @ ErrorCode::ReportFinalized
+    )]
+    pub safety_report: Account<'info, SafetyReport>,
+
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetReportFlags<'info> {
+    #[account(
+        seeds = [b"safety_report", safety_report.token_mint.as_ref(), authority.key().as_ref(), safety_report.slot_seed()],
+        bump = safety_report.bump,
+        has_one = authority,
+        constraint = !safety_report.finalized @ ErrorCode::ReportFinalized
+    )]
+    pub safety_report: Account<'info, SafetyReport>,
+
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = 8 + ReportFlags::INIT_SPACE,
+        seeds = [b"report_flags", safety_report.token_mint.as_ref(), authority.key().as_ref(), safety_report.slot_seed()],
+        bump
+    )]
+    pub report_flags: Account<'info, ReportFlags>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct GetRiskTrend<'info> {
+    #[account(
+        seeds = [b"safety_report", safety_report.token_mint.as_ref(), safety_report.authority.as_ref(), safety_report.slot_seed()],
+        bump = safety_report.bump
+    )]
+    pub safety_report: Account<'info, SafetyReport>,
+
+    #[account(
+        seeds = [b"report_history", safety_report.token_mint.as_ref(), safety_report.authority.as_ref(), safety_report.slot_seed()],
+        bump = report_history.bump
+    )]
+    pub report_history: Account<'info, ReportHistory>,
+}
+
+#[derive(Accounts)]
+pub struct EndorseReport<'info> {
+    #[account(
+        mut,
+        seeds = [b"safety_report", safety_report.token_mint.as_ref(), safety_report.authority.as_ref(), safety_report.slot_seed()],
+        bump = safety_report.bump
+    )]
+    pub safety_report: Account<'info, SafetyReport>,
+
+    /// Fails to init if this endorser has already voted on the report.
+    #[account(
+        init,
+        payer = endorser,
+        space = 8 + Endorsement::INIT_SPACE,
+        seeds = [b"endorsement", safety_report.key().as_ref(), endorser.key().as_ref()],
+        bump
+    )]
+    pub endorsement: Account<'info, Endorsement>,
+
+    /// Only registry authorities may endorse.
+    #[account(
+        seeds = [b"registry", endorser.key().as_ref()],
+        bump = endorser_registry.bump,
+        constraint = endorser_registry.authority == endorser.key() @ ErrorCode::Unauthorized
+    )]
+    pub endorser_registry: Account<'info, Registry>,
+
+    #[account(mut)]
+    pub endorser: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ResolveDispute<'info> {
+    #[account(
+        mut,
+        seeds = [b"subscription_config"],
+        bump = subscription_config.bump,
+        has_one = admin @ ErrorCode::Unauthorized
+    )]
+    pub subscription_config: Account<'info, SubscriptionConfig>,
+
+    #[account(
+        mut,
+        seeds = [b"safety_report", safety_report.token_mint.as_ref(), safety_report.authority.as_ref(), safety_report.slot_seed()],
+        bump = safety_report.bump,
+        constraint = !safety_report.finalized @ ErrorCode::ReportFinalized
+    )]
+    pub safety_report: Account<'info, SafetyReport>,
+
+    #[account(
+        mut,
+        seeds = [b"global_stats"],
+        bump = global_stats.bump
+    )]
+    pub global_stats: Account<'info, GlobalStats>,
+
+    #[account(
+        mut,
+        seeds = [b"consensus", safety_report.token_mint.as_ref()],
+        bump = consensus.bump
+    )]
+    pub consensus: Account<'info, TokenConsensus>,
+
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct GetReport<'info> {
+    #[account(
+        seeds = [b"safety_report", safety_report.token_mint.as_ref(), safety_report.authority.as_ref(), safety_report.slot_seed()],
+        bump = safety_report.bump
+    )]
+    pub safety_report: Account<'info, SafetyReport>,
+}
+
+#[derive(Accounts)]
+pub struct GetVerdict<'info> {
+    #[account(
+        seeds = [b"safety_report", safety_report.token_mint.as_ref(), safety_report.authority.as_ref(), safety_report.slot_seed()],
+        bump = safety_report.bump
+    )]
+    pub safety_report: Account<'info, SafetyReport>,
+
+    #[account(
+        seeds = [b"registry", safety_report.authority.as_ref()],
+        bump = registry.bump
+    )]
+    pub registry: Account<'info, Registry>,
+}
+
+#[derive(Accounts)]
+pub struct GetGlobalStats<'info> {
+    #[account(
+        seeds = [b"global_stats"],
+        bump = global_stats.bump
+    )]
+    pub global_stats: Account<'info, GlobalStats>,
+}
+
+#[derive(Accounts)]
+pub struct GetConsensus<'info> {
+    #[account(
+        seeds = [b"consensus", consensus.token_mint.as_ref()],
+        bump = consensus.bump
+    )]
+    pub consensus: Account<'info, TokenConsensus>,
+}
+
+#[derive(Accounts)]
+pub struct GetConventions {}
+
+#[derive(Accounts)]
+pub struct GetTokenReports<'info> {
+    #[account(
+        seeds = [b"token_index", token_index.token_mint.as_ref()],
+        bump = token_index.bump
+    )]
+    pub token_index: Account<'info, TokenReportIndex>,
+}
+
+#[derive(Accounts)]
+pub struct ReadGatedReport<'info> {
+    #[account(
+        seeds = [b"safety_report", safety_report.token_mint.as_ref(), safety_report.authority.as_ref(), safety_report.slot_seed()],
+        bump = safety_report.bump
+    )]
+    pub safety_report: Account<'info, SafetyReport>,
+
+    #[account(
+        mut,
+        seeds = [b"subscription", user.key().as_ref()],
+        bump = subscription.bump,
+        has_one = user
+    )]
+    pub subscription: Account<'info, Subscription>,
+
+    #[account(
+        seeds = [b"subscription_config"],
+        bump = subscription_config.bump
+    )]
+    pub subscription_config: Account<'info, SubscriptionConfig>,
+
+    pub user: Signer<'info>,
+}
+
+// ============================================================================
+// Account Contexts - Subscriptions
+// ============================================================================
+
+#[derive(Accounts)]
+pub struct InitializeSubscriptionConfig<'info> {
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + SubscriptionConfig::INIT_SPACE,
+        seeds = [b"subscription_config"],
+        bump
+    )]
+    pub subscription_config: Account<'info, SubscriptionConfig>,
+
+    /// CHECK: Program-derived treasury that receives SOL payments. Holds no data.
+    #[account(
+        mut,
+        seeds = [b"treasury"],
+        bump
+    )]
+    pub treasury: AccountInfo<'info>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(tier: u8, referrer: Option<Pubkey>, coupon_code: Option<String>)]
+pub struct Subscribe<'info> {
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = 8 + Subscription::INIT_SPACE,
+        seeds = [b"subscription", user.key().as_ref()],
+        bump
+    )]
+    pub subscription: Account<'info, Subscription>,
+
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = 8 + SubscriberMeta::INIT_SPACE,
+        seeds = [b"subscriber_meta", user.key().as_ref()],
+        bump
+    )]
+    pub subscriber_meta: Account<'info, SubscriberMeta>,
+
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = 8 + SubscriptionHistory::INIT_SPACE,
+        seeds = [b"sub_history", user.key().as_ref()],
+        bump
+    )]
+    pub subscription_history: Account<'info, SubscriptionHistory>,
+
+    #[account(
+        mut,
+        seeds = [b"subscription_config"],
+        bump = subscription_config.bump
+    )]
+    pub subscription_config: Account<'info, SubscriptionConfig>,
+
+    /// CHECK: Treasury to receive payment.
+    #[account(
+        mut,
+        constraint = treasury.key() == subscription_config.treasury @ ErrorCode::InvalidTreasury
+    )]
+    pub treasury: AccountInfo<'info>,
+
+    /// Required when subscribing with a referrer.
+    #[account(
+        mut,
+        seeds = [b"referral", referral.referrer.as_ref()],
+        bump = referral.bump
+    )]
+    pub referral: Option<Account<'info, Referral>>,
+
+    /// CHECK: Referrer's wallet, checked against `referrer` in the handler.
+    #[account(mut)]
+    pub referrer_wallet: Option<UncheckedAccount<'info>>,
+
+    /// Optional discount code; its PDA is derived from `keccak(coupon_code)`.
+    #[account(
+        mut,
+        seeds = [b"coupon", coupon_seed(&coupon_code).as_ref()],
+        bump = coupon.bump
+    )]
+    pub coupon: Option<Account<'info, Coupon>>,
+
+    /// When set, payments are split across its recipients instead of going to the
+    /// treasury. Required once the admin has called set_revenue_split.
+    #[account(
+        seeds = [b"revenue_split"],
+        bump = revenue_split.bump
+    )]
+    pub revenue_split: Option<Account<'info, RevenueSplit>>,
+
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct GetSubscriptionHistory<'info> {
+    #[account(
+        seeds = [b"sub_history", user.key().as_ref()],
+        bump = subscription_history.bump,
+        has_one = user @ ErrorCode::Unauthorized
+    )]
+    pub subscription_history: Account<'info, SubscriptionHistory>,
+
+    pub user: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct GetLoyalty<'info> {
+    #[account(
+        seeds = [b"subscription", subscription.user.as_ref()],
+        bump = subscription.bump
+    )]
+    pub subscription: Account<'info, Subscription>,
+}
+
+#[derive(Accounts)]
+pub struct RegisterReferrer<'info> {
+    #[account(
+        init,
+        payer = referrer,
+        space = 8 + Referral::INIT_SPACE,
+        seeds = [b"referral", referrer.key().as_ref()],
+        bump
+    )]
+    pub referral: Account<'info, Referral>,
+
+    #[account(mut)]
+    pub referrer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SubscribeSpl<'info> {
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = 8 + Subscription::INIT_SPACE,
+        seeds = [b"subscription", user.key().as_ref()],
+        bump
+    )]
+    pub subscription: Account<'info, Subscription>,
+
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = 8 + SubscriberMeta::INIT_SPACE,
+        seeds = [b"subscriber_meta", user.key().as_ref()],
+        bump
+    )]
+    pub subscriber_meta: Account<'info, SubscriberMeta>,
+
+    #[account(
+        mut,
+        seeds = [b"subscription_config"],
+        bump = subscription_config.bump
+    )]
+    pub subscription_config: Account<'info, SubscriptionConfig>,
+
+    #[account(
+        mut,
+        seeds = [b"accepted_mint", payment_mint.key().as_ref()],
+        bump = accepted_mint.bump
+    )]
+    pub accepted_mint: Account<'info, AcceptedMint>,
+
+    pub payment_mint: Account<'info, Mint>,
+
+    #[account(
+        mut,
+        constraint = user_token_account.mint == payment_mint.key() @ ErrorCode::InvalidPaymentMint,
+        constraint = user_token_account.owner == user.key() @ ErrorCode::Unauthorized
+    )]
+    pub user_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = treasury_token_account.mint == payment_mint.key() @ ErrorCode::InvalidPaymentMint,
+        constraint = treasury_token_account.owner == subscription_config.token_treasury @ ErrorCode::InvalidTreasury
+    )]
+    pub treasury_token_account: Account<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SubscribeLifetime<'info> {
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = 8 + Subscription::INIT_SPACE,
+        seeds = [b"subscription", user.key().as_ref()],
+        bump
+    )]
+    pub subscription: Account<'info, Subscription>,
+
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = 8 + SubscriberMeta::INIT_SPACE,
+        seeds = [b"subscriber_meta", user.key().as_ref()],
+        bump
+    )]
+    pub subscriber_meta: Account<'info, SubscriberMeta>,
+
+    #[account(
+        mut,
+        seeds = [b"subscription_config"],
+        bump = subscription_config.bump
+    )]
+    pub subscription_config: Account<'info, SubscriptionConfig>,
+
+    /// CHECK: Treasury to receive payment.
+    #[account(
+        mut,
+        constraint = treasury.key() == subscription_config.treasury @ ErrorCode::InvalidTreasury
+    )]
+    pub treasury: AccountInfo<'info>,
+
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SubscribeAnnual<'info> {
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = 8 + Subscription::INIT_SPACE,
+        seeds = [b"subscription", user.key().as_ref()],
+        bump
+    )]
+    pub subscription: Account<'info, Subscription>,
+
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = 8 + SubscriberMeta::INIT_SPACE,
+        seeds = [b"subscriber_meta", user.key().as_ref()],
+        bump
+    )]
+    pub subscriber_meta: Account<'info, SubscriberMeta>,
+
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = 8 + SubscriptionHistory::INIT_SPACE,
+        seeds = [b"sub_history", user.key().as_ref()],
+        bump
+    )]
+    pub subscription_history: Account<'info, SubscriptionHistory>,
+
+    #[account(
+        mut,
+        seeds = [b"subscription_config"],
+        bump = subscription_config.bump
+    )]
+    pub subscription_config: Account<'info, SubscriptionConfig>,
+
+    /// CHECK: Treasury to receive payment.
+    #[account(
+        mut,
+        constraint = treasury.key() == subscription_config.treasury @ ErrorCode::InvalidTreasury
+    )]
+    pub treasury: AccountInfo<'info>,
+
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct MintReceipt<'info> {
+    #[account(
+        mut,
+        seeds = [b"subscription", user.key().as_ref()],
+        bump = subscription.bump,
+        has_one = user @ ErrorCode::Unauthorized
+    )]
+    pub subscription: Account<'info, Subscription>,
+
+    #[account(
+        init,
+        payer = user,
+        seeds = [b"receipt", subscription.key().as_ref()],
+        bump,
+        mint::decimals = 0,
+        mint::authority = subscription,
+        mint::freeze_authority = subscription
+    )]
+    pub receipt_mint: Account<'info, Mint>,
+
+    #[account(
+        init,
+        payer = user,
+        associated_token::mint = receipt_mint,
+        associated_token::authority = user
+    )]
+    pub receipt_token: Account<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct StartTrial<'info> {
+    #[account(
+        init,
+        payer = user,
+        space = 8 + Subscription::INIT_SPACE,
+        seeds = [b"subscription", user.key().as_ref()],
+        bump
+    )]
+    pub subscription: Account<'info, Subscription>,
+
+    /// Fails to init if the user has already had a trial.
+    #[account(
+        init,
+        payer = user,
+        space = 8 + TrialRecord::INIT_SPACE,
+        seeds = [b"trial", user.key().as_ref()],
+        bump
+    )]
+    pub trial_record: Account<'info, TrialRecord>,
+
+    #[account(
+        seeds = [b"subscription_config"],
+        bump = subscription_config.bump
+    )]
+    pub subscription_config: Account<'info, SubscriptionConfig>,
+
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RenewSubscription<'info> {
+    #[account(
+        mut,
+        seeds = [b"subscription", user.key().as_ref()],
+        bump = subscription.bump,
+        has_one = user @ ErrorCode::Unauthorized
+    )]
+    pub subscription: Account<'info, Subscription>,
+
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = 8 + SubscriptionHistory::INIT_SPACE,
+        seeds = [b"sub_history", user.key().as_ref()],
+        bump
+    )]
+    pub subscription_history: Account<'info, SubscriptionHistory>,
+
+    #[account(
+        mut,
+        seeds = [b"subscription_config"],
+        bump = subscription_config.bump
+    )]
+    pub subscription_config: Account<'info, SubscriptionConfig>,
+
+    /// CHECK: Treasury to receive payment.
+    #[account(
+        mut,
+        constraint = treasury.key() == subscription_config.treasury @ ErrorCode::InvalidTreasury
+    )]
+    pub treasury: AccountInfo<'info>,
+
+    /// When set, payments are split across its recipients instead of going to the
+    /// treasury. Required once the admin has called set_revenue_split.
+    #[account(
+        seeds = [b"revenue_split"],
+        bump = revenue_split.bump
+    )]
+    pub revenue_split: Option<Account<'info, RevenueSplit>>,
+
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(recipient: Pubkey)]
+pub struct GiftSubscription<'info> {
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = 8 + Subscription::INIT_SPACE,
+        seeds = [b"subscription", recipient.as_ref()],
+        bump
+    )]
+    pub subscription: Account<'info, Subscription>,
+
+    /// The payer's rate-limit state; a gift counts as their subscribe, so
+    /// gifting can't lock the recipient out of their own subscribe/cancel.
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = 8 + SubscriberMeta::INIT_SPACE,
+        seeds = [b"subscriber_meta", payer.key().as_ref()],
+        bump
+    )]
+    pub subscriber_meta: Account<'info, SubscriberMeta>,
+
+    #[account(
+        mut,
+        seeds = [b"subscription_config"],
+        bump = subscription_config.bump
+    )]
+    pub subscription_config: Account<'info, SubscriptionConfig>,
+
+    /// CHECK: Treasury to receive payment.
+    #[account(
+        mut,
+        constraint = treasury.key() == subscription_config.treasury @ ErrorCode::InvalidTreasury
+    )]
+    pub treasury: AccountInfo<'info>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimFreeSubscription<'info> {
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = 8 + Subscription::INIT_SPACE,
+        seeds = [b"subscription", user.key().as_ref()],
+        bump
+    )]
+    pub subscription: Account<'info, Subscription>,
+
+    #[account(
+        mut,
+        seeds = [b"whitelist", user.key().as_ref()],
+        bump = whitelist.bump
+    )]
+    pub whitelist: Account<'info, Whitelist>,
+
+    #[account(
+        mut,
+        seeds = [b"subscription_config"],
+        bump = subscription_config.bump
+    )]
+    pub subscription_config: Account<'info, SubscriptionConfig>,
+
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimWhitelisted<'info> {
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = 8 + Subscription::INIT_SPACE,
+        seeds = [b"subscription", user.key().as_ref()],
+        bump
+    )]
+    pub subscription: Account<'info, Subscription>,
+
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = 8 + MerkleClaim::INIT_SPACE,
+        seeds = [b"merkle_claim", subscription_config.whitelist_root.as_ref(), user.key().as_ref()],
+        bump
+    )]
+    pub merkle_claim: Account<'info, MerkleClaim>,
+
+    #[account(
+        mut,
+        seeds = [b"subscription_config"],
+        bump = subscription_config.bump
+    )]
+    pub subscription_config: Account<'info, SubscriptionConfig>,
+
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct AddToWatchlist<'info> {
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = 8 + Watchlist::INIT_SPACE,
+        seeds = [b"watchlist", user.key().as_ref()],
+        bump
+    )]
+    pub watchlist: Account<'info, Watchlist>,
+
+    #[account(
+        seeds = [b"subscription", user.key().as_ref()],
+        bump = subscription.bump,
+        has_one = user @ ErrorCode::Unauthorized
+    )]
+    pub subscription: Account<'info, Subscription>,
+
+    #[account(
+        seeds = [b"subscription_config"],
+        bump = subscription_config.bump
+    )]
+    pub subscription_config: Account<'info, SubscriptionConfig>,
+
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RemoveFromWatchlist<'info> {
+    #[account(
+        mut,
+        seeds = [b"watchlist", user.key().as_ref()],
+        bump = watchlist.bump,
+        has_one = user @ ErrorCode::Unauthorized
+    )]
+    pub watchlist: Account<'info, Watchlist>,
+
+    pub user: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetAutoRenew<'info> {
+    #[account(
+        mut,
+        seeds = [b"subscription", user.key().as_ref()],
+        bump = subscription.bump,
+        has_one = user @ ErrorCode::Unauthorized
+    )]
+    pub subscription: Account<'info, Subscription>,
+
+    pub user: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct DisableSubscription<'info> {
+    #[account(
+        mut,
+        seeds = [b"subscription", user.key().as_ref()],
+        bump = subscription.bump,
+        has_one = user @ ErrorCode::Unauthorized
+    )]
+    pub subscription: Account<'info, Subscription>,
+
+    pub user: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct FundEscrow<'info> {
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = 8 + Escrow::INIT_SPACE,
+        seeds = [b"escrow", user.key().as_ref()],
+        bump
+    )]
+    pub escrow: Account<'info, Escrow>,
+
+    #[account(
+        seeds = [b"subscription_config"],
+        bump = subscription_config.bump
+    )]
+    pub subscription_config: Account<'info, SubscriptionConfig>,
+
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawEscrow<'info> {
+    #[account(
+        mut,
+        seeds = [b"escrow", user.key().as_ref()],
+        bump = escrow.bump,
+        has_one = user @ ErrorCode::Unauthorized
+    )]
+    pub escrow: Account<'info, Escrow>,
+
+    #[account(
+        seeds = [b"subscription_config"],
+        bump = subscription_config.bump
+    )]
+    pub subscription_config: Account<'info, SubscriptionConfig>,
+
+    #[account(mut)]
+    pub user: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CrankRenew<'info> {
+    #[account(
+        mut,
+        seeds = [b"subscription", subscription.user.as_ref()],
+        bump = subscription.bump
+    )]
+    pub subscription: Account<'info, Subscription>,
+
+    #[account(
+        mut,
+        seeds = [b"subscription_config"],
+        bump = subscription_config.bump
+    )]
+    pub subscription_config: Account<'info, SubscriptionConfig>,
+
+    /// Lamports pre-funded by the user to pay for renewals.
+    #[account(
+        mut,
+        seeds = [b"escrow", subscription.user.as_ref()],
+        bump = escrow.bump
+    )]
+    pub escrow: Account<'info, Escrow>,
+
+    /// CHECK: Treasury to receive payment.
+    #[account(
+        mut,
+        constraint = treasury.key() == subscription_config.treasury @ ErrorCode::InvalidTreasury
+    )]
+    pub treasury: AccountInfo<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CancelSubscription<'info> {
+    #[account(
+        mut,
+        seeds = [b"subscription", user.key().as_ref()],
+        bump = subscription.bump,
+        has_one = user @ ErrorCode::Unauthorized
+    )]
+    pub subscription: Account<'info, Subscription>,
+
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = 8 + SubscriberMeta::INIT_SPACE,
+        seeds = [b"subscriber_meta", user.key().as_ref()],
+        bump
+    )]
+    pub subscriber_meta: Account<'info, SubscriberMeta>,
+
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = 8 + SubscriptionHistory::INIT_SPACE,
+        seeds = [b"sub_history", user.key().as_ref()],
+        bump
+    )]
+    pub subscription_history: Account<'info, SubscriptionHistory>,
+
+    #[account(
+        mut,
+        seeds = [b"subscription_config"],
+        bump = subscription_config.bump
+    )]
+    pub subscription_config: Account<'info, SubscriptionConfig>,
+
+    /// CHECK: Program-derived treasury; the program signs the refund with its seeds.
+    #[account(
+        mut,
+        seeds = [b"treasury"],
+        bump = subscription_config.treasury_bump
+    )]
+    pub treasury: AccountInfo<'info>,
+
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(new_owner: Pubkey)]
+pub struct TransferSubscription<'info> {
+    #[account(
+        mut,
+        close = user,
+        seeds = [b"subscription", user.key().as_ref()],
+        bump = subscription.bump,
+        has_one = user @ ErrorCode::Unauthorized
+    )]
+    pub subscription: Account<'info, Subscription>,
+
+    /// Fails to init if `new_owner` already has a subscription.
+    #[account(
+        init,
+        payer = user,
+        space = 8 + Subscription::INIT_SPACE,
+        seeds = [b"subscription", new_owner.as_ref()],
+        bump
+    )]
+    pub new_subscription: Account<'info, Subscription>,
+
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CloseSubscription<'info> {
+    #[account(
+        mut,
+        close = user,
+        seeds = [b"subscription", user.key().as_ref()],
         bump = subscription.bump,
         has_one = user @ ErrorCode::Unauthorized
     )]
     pub subscription: Account<'info, Subscription>,
 
-    #[account(
-        mut,
-        seeds = [b"subscription_config"],
-        bump = subscription_config.bump
-    )]
-    pub subscription_config: Account<'info, SubscriptionConfig>,
+    #[account(
+        mut,
+        seeds = [b"subscription_config"],
+        bump = subscription_config.bump
+    )]
+    pub subscription_config: Account<'info, SubscriptionConfig>,
+
+    /// Receives the reclaimed rent; need not sign.
+    #[account(mut)]
+    pub user: SystemAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct VerifySubscription<'info> {
+    #[account(
+        mut,
+        seeds = [b"subscription", subscription.user.as_ref()],
+        bump = subscription.bump
+    )]
+    pub subscription: Account<'info, Subscription>,
+
+    #[account(
+        seeds = [b"subscription_config"],
+        bump = subscription_config.bump
+    )]
+    pub subscription_config: Account<'info, SubscriptionConfig>,
+}
+
+#[derive(Accounts)]
+pub struct VerifySubscriptionsBatch<'info> {
+    #[account(
+        seeds = [b"subscription_config"],
+        bump = subscription_config.bump
+    )]
+    pub subscription_config: Account<'info, SubscriptionConfig>,
+}
+
+#[derive(Accounts)]
+pub struct GetPricing<'info> {
+    #[account(
+        seeds = [b"subscription_config"],
+        bump = subscription_config.bump
+    )]
+    pub subscription_config: Account<'info, SubscriptionConfig>,
+}
+
+#[derive(Accounts)]
+pub struct UpdatePricing<'info> {
+    #[account(
+        mut,
+        seeds = [b"subscription_config"],
+        bump = subscription_config.bump,
+        has_one = admin @ ErrorCode::Unauthorized
+    )]
+    pub subscription_config: Account<'info, SubscriptionConfig>,
+
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct AddAcceptedMint<'info> {
+    #[account(
+        mut,
+        seeds = [b"subscription_config"],
+        bump = subscription_config.bump,
+        has_one = admin @ ErrorCode::Unauthorized
+    )]
+    pub subscription_config: Account<'info, SubscriptionConfig>,
+
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + AcceptedMint::INIT_SPACE,
+        seeds = [b"accepted_mint", mint.key().as_ref()],
+        bump
+    )]
+    pub accepted_mint: Account<'info, AcceptedMint>,
+
+    pub mint: Account<'info, Mint>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RemoveAcceptedMint<'info> {
+    #[account(
+        mut,
+        seeds = [b"subscription_config"],
+        bump = subscription_config.bump,
+        has_one = admin @ ErrorCode::Unauthorized
+    )]
+    pub subscription_config: Account<'info, SubscriptionConfig>,
+
+    #[account(
+        mut,
+        close = admin,
+        seeds = [b"accepted_mint", accepted_mint.mint.as_ref()],
+        bump = accepted_mint.bump
+    )]
+    pub accepted_mint: Account<'info, AcceptedMint>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawTreasury<'info> {
+    #[account(
+        mut,
+        seeds = [b"subscription_config"],
+        bump = subscription_config.bump,
+        has_one = admin @ ErrorCode::Unauthorized
+    )]
+    pub subscription_config: Account<'info, SubscriptionConfig>,
+
+    /// CHECK: Program-derived treasury; the program signs for it with its seeds.
+    #[account(
+        mut,
+        seeds = [b"treasury"],
+        bump = subscription_config.treasury_bump
+    )]
+    pub treasury: AccountInfo<'info>,
+
+    /// CHECK: Destination chosen by the admin.
+    #[account(mut)]
+    pub destination: AccountInfo<'info>,
+
+    pub admin: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SweepTreasury<'info> {
+    #[account(
+        mut,
+        seeds = [b"subscription_config"],
+        bump = subscription_config.bump,
+        has_one = admin @ ErrorCode::Unauthorized
+    )]
+    pub subscription_config: Account<'info, SubscriptionConfig>,
+
+    /// CHECK: Program-derived treasury; the program signs for it with its seeds.
+    #[account(
+        mut,
+        seeds = [b"treasury"],
+        bump = subscription_config.treasury_bump
+    )]
+    pub treasury: AccountInfo<'info>,
+
+    /// CHECK: New treasury chosen by the admin; receives the swept balance.
+    #[account(mut)]
+    pub new_treasury: AccountInfo<'info>,
+
+    pub admin: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(code_hash: [u8; 32])]
+pub struct CreateCoupon<'info> {
+    #[account(
+        mut,
+        seeds = [b"subscription_config"],
+        bump = subscription_config.bump,
+        has_one = admin @ ErrorCode::Unauthorized
+    )]
+    pub subscription_config: Account<'info, SubscriptionConfig>,
+
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + Coupon::INIT_SPACE,
+        seeds = [b"coupon", code_hash.as_ref()],
+        bump
+    )]
+    pub coupon: Account<'info, Coupon>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(user: Pubkey)]
+pub struct AddToWhitelist<'info> {
+    #[account(
+        mut,
+        seeds = [b"subscription_config"],
+        bump = subscription_config.bump,
+        has_one = admin @ ErrorCode::Unauthorized
+    )]
+    pub subscription_config: Account<'info, SubscriptionConfig>,
+
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + Whitelist::INIT_SPACE,
+        seeds = [b"whitelist", user.as_ref()],
+        bump
+    )]
+    pub whitelist: Account<'info, Whitelist>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(user: Pubkey)]
+pub struct RemoveFromWhitelist<'info> {
+    #[account(
+        mut,
+        seeds = [b"subscription_config"],
+        bump = subscription_config.bump,
+        has_one = admin @ ErrorCode::Unauthorized
+    )]
+    pub subscription_config: Account<'info, SubscriptionConfig>,
+
+    #[account(
+        mut,
+        close = admin,
+        seeds = [b"whitelist", user.as_ref()],
+        bump = whitelist.bump
+    )]
+    pub whitelist: Account<'info, Whitelist>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct MigrateSubscriptionConfig<'info> {
+    /// CHECK: An older layout does not deserialize as SubscriptionConfig, so
+    /// migrate_subscription_config decodes it and checks its admin itself.
+    #[account(
+        mut,
+        owner = crate::ID,
+        seeds = [b"subscription_config"],
+        bump
+    )]
+    pub subscription_config: UncheckedAccount<'info>,
+
+    /// CHECK: Only whether it exists is read, to set `revenue_split_set`.
+    #[account(seeds = [b"revenue_split"], bump)]
+    pub revenue_split: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct MigrateSubscription<'info> {
+    /// CHECK: An older layout does not deserialize as Subscription, so
+    /// migrate_subscription decodes it and checks its user itself.
+    #[account(
+        mut,
+        owner = crate::ID,
+        seeds = [b"subscription", user.key().as_ref()],
+        bump
+    )]
+    pub subscription: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetRevenueSplit<'info> {
+    #[account(
+        mut,
+        seeds = [b"subscription_config"],
+        bump = subscription_config.bump,
+        has_one = admin @ ErrorCode::Unauthorized
+    )]
+    pub subscription_config: Account<'info, SubscriptionConfig>,
+
+    #[account(
+        init_if_needed,
+        payer = admin,
+        space = 8 + RevenueSplit::INIT_SPACE,
+        seeds = [b"revenue_split"],
+        bump
+    )]
+    pub revenue_split: Account<'info, RevenueSplit>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(user: Pubkey)]
+pub struct RevokeSubscription<'info> {
+    #[account(
+        mut,
+        seeds = [b"subscription_config"],
+        bump = subscription_config.bump,
+        has_one = admin @ ErrorCode::Unauthorized
+    )]
+    pub subscription_config: Account<'info, SubscriptionConfig>,
+
+    #[account(
+        mut,
+        seeds = [b"subscription", user.as_ref()],
+        bump = subscription.bump
+    )]
+    pub subscription: Account<'info, Subscription>,
+
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(user: Pubkey)]
+pub struct AdminRefund<'info> {
+    #[account(
+        mut,
+        seeds = [b"subscription_config"],
+        bump = subscription_config.bump,
+        has_one = admin @ ErrorCode::Unauthorized
+    )]
+    pub subscription_config: Account<'info, SubscriptionConfig>,
+
+    #[account(
+        mut,
+        seeds = [b"subscription", user.as_ref()],
+        bump = subscription.bump
+    )]
+    pub subscription: Account<'info, Subscription>,
+
+    /// CHECK: Program-derived treasury; the program signs the refund with its seeds.
+    #[account(
+        mut,
+        seeds = [b"treasury"],
+        bump = subscription_config.treasury_bump
+    )]
+    pub treasury: AccountInfo<'info>,
+
+    #[account(
+        mut,
+        constraint = user_wallet.key() == user @ ErrorCode::Unauthorized
+    )]
+    pub user_wallet: SystemAccount<'info>,
+
+    #[account(
+        init_if_needed,
+        payer = admin,
+        space = 8 + RefundRecord::INIT_SPACE,
+        seeds = [b"refund", user.as_ref()],
+        bump
+    )]
+    pub refund_record: Account<'info, RefundRecord>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct AutoPauseIfStale<'info> {
+    #[account(
+        mut,
+        seeds = [b"subscription_config"],
+        bump = subscription_config.bump
+    )]
+    pub subscription_config: Account<'info, SubscriptionConfig>,
+}
+
+/// Shared context for admin-only updates to the subscription config.
+#[derive(Accounts)]
+pub struct AdminConfig<'info> {
+    #[account(
+        mut,
+        seeds = [b"subscription_config"],
+        bump = subscription_config.bump,
+        has_one = admin @ ErrorCode::Unauthorized
+    )]
+    pub subscription_config: Account<'info, SubscriptionConfig>,
+
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct AcceptAdmin<'info> {
+    #[account(
+        mut,
+        seeds = [b"subscription_config"],
+        bump = subscription_config.bump,
+        constraint = subscription_config.pending_admin == Some(new_admin.key()) @ ErrorCode::Unauthorized
+    )]
+    pub subscription_config: Account<'info, SubscriptionConfig>,
+
+    pub new_admin: Signer<'info>,
+}
+
+// ============================================================================
+// Account Structs - Registry
+// ============================================================================
+
+/// Serialized as a single byte: High=0, Medium=1, Low=2.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, InitSpace)]
+pub enum RiskLevel {
+    High,
+    Medium,
+    Low,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct SafetyReport {
+    pub version: u8,          // Layout version, first so its offset never moves
+    pub authority: Pubkey,
+    pub token_mint: Pubkey,
+    pub risk_score: u8,       // 0-100 (higher = safer)
+    pub risk_level: RiskLevel,
+    pub flags: u32,           // Bitmask of FLAG_* risk categories
+    pub flags_count: u8,      // flags.count_ones()
+    #[max_len(64)]
+    pub protocol_name: String,
+    #[max_len(200)]
+    pub report_uri: String,   // Off-chain detailed report, empty if none
+    pub timestamp: i64,       // Last submitted/updated
+    pub created_at: i64,      // Set once by submit_report
+    pub endorsements: u32,    // Other authorities agreeing with this report
+    pub disputes: u32,        // Other authorities disputing it
+    pub confidence: u8,       // % of votes that are endorsements; DEFAULT_CONFIDENCE with no votes
+    pub min_tier: u8,         // Subscription tier read_gated_report requires; 0 = public
+    pub bump: u8,
+    pub resolved: bool,       // Settled by resolve_dispute; no further endorsements
+    pub active: bool,         // False = hidden by set_report_active; read instructions treat it as absent
+    pub category: u8,         // CATEGORY_* protocol type
+    pub finalized: bool,      // Frozen by finalize_report; no further changes or closing
+    pub report_slot: u8,      // PDA seed suffix; 0 = the authority's default report for the mint
+    pub cosigner: Option<Pubkey>, // Registry signer that co-signed the last submit, update or upsert
+}
+
+impl SafetyReport {
+    /// Last PDA seed of this report (and its ReportHistory / ReportFlags); see report_slot_seed.
+    pub fn slot_seed(&self) -> &[u8] {
+        report_slot_seed(&self.report_slot)
+    }
+
+    /// Whether the report counts in its mint's TokenConsensus: live, and the
+    /// authority's default (slot 0) report, so an authority counts once per mint
+    /// however many slots it uses.
+    pub fn in_consensus(&self) -> bool {
+        self.active && self.report_slot == 0
+    }
+
+    /// Whether the score is on the safe side of `threshold` under SCORE_CONVENTION
+    /// (at or above it while higher scores are safer).
+    pub fn is_safe(&self, threshold: u8) -> bool {
+        if SCORE_CONVENTION == SCORE_HIGHER_IS_SAFER {
+            self.risk_score >= threshold
+        } else {
+            self.risk_score <= threshold
+        }
+    }
+
+    /// A freshly submitted report of `authority` from `input`, timestamped `now`,
+    /// in slot 0: the upsert, batch, multisig, delegated and lite paths that use
+    /// this only write an authority's default report.
+    pub fn new(authority: Pubkey, input: ReportInput, now: i64, bump: u8) -> Self {
+        Self {
+            authority,
+            token_mint: input.token_mint,
+            risk_score: input.risk_score,
+            risk_level: input.risk_level,
+            flags: input.flags,
+            flags_count: input.flags.count_ones() as u8,
+            category: input.category,
+            protocol_name: input.protocol_name,
+            report_uri: input.report_uri,
+            timestamp: now,
+            created_at: now,
+            endorsements: 0,
+            disputes: 0,
+            confidence: DEFAULT_CONFIDENCE,
+            min_tier: input.min_tier,
+            resolved: false,
+            active: true,
+            finalized: false,
+            report_slot: 0,
+            cosigner: None,
+            version: CURRENT_VERSION,
+            bump,
+        }
+    }
+
+    /// Whether the given FLAG_* bit is set on this report.
+    pub fn has_flag(&self, bit: u8) -> bool {
+        bit < 32 && self.flags & (1u32 << bit) != 0
+    }
+
+    /// Recomputes `confidence` as 100 * endorsements / (endorsements + disputes).
+    pub fn refresh_confidence(&mut self) {
+        let votes = self.endorsements as u64 + self.disputes as u64;
+        self.confidence = match (100 * self.endorsements as u64).checked_div(votes) {
+            Some(pct) => pct as u8,
+            None => DEFAULT_CONFIDENCE, // No votes yet
+        };
+    }
+}
+
+/// SafetyReport as v0.2.0 stored it, before `version`; read only by migrate_report.
+#[derive(AnchorDeserialize, InitSpace)]
+pub struct LegacySafetyReport {
+    pub authority: Pubkey,
+    pub token_mint: Pubkey,
+    pub risk_score: u8,
+    pub risk_level: RiskLevel, // Stored as a u8 with the same 0=HIGH..2=LOW encoding
+    pub flags_count: u8,
+    #[max_len(32)]
+    pub protocol_name: String,
+    pub timestamp: i64,
+    pub bump: u8,
+}
+
+impl LegacySafetyReport {
+    /// The report as version 1 would hold it. v0.2.0 kept only a flag count,
+    /// not which flags were set, so it comes out with no flags.
+    fn into_v1(self) -> SafetyReportV1 {
+        SafetyReportV1 {
+            version: 0,
+            authority: self.authority,
+            token_mint: self.token_mint,
+            risk_score: self.risk_score,
+            risk_level: self.risk_level,
+            flags: 0,
+            flags_count: 0,
+            protocol_name: self.protocol_name,
+            report_uri: String::new(),
+            timestamp: self.timestamp,
+            created_at: self.timestamp,
+            endorsements: 0,
+            disputes: 0,
+            confidence: DEFAULT_CONFIDENCE,
+            min_tier: 0,
+            bump: self.bump,
+        }
+    }
+}
+
+/// The SafetyReport fields of version 1, which every later version appends to;
+/// read only by migrate_report.
+#[derive(AnchorDeserialize)]
+pub struct SafetyReportV1 {
+    pub version: u8,
+    pub authority: Pubkey,
+    pub token_mint: Pubkey,
+    pub risk_score: u8,
+    pub risk_level: RiskLevel,
+    pub flags: u32,
+    pub flags_count: u8,
+    pub protocol_name: String,
+    pub report_uri: String,
+    pub timestamp: i64,
+    pub created_at: i64,
+    pub endorsements: u32,
+    pub disputes: u32,
+    pub confidence: u8,
+    pub min_tier: u8,
+    pub bump: u8,
+}
+
+impl SafetyReportV1 {
+    /// The current SafetyReport, with a new report's defaults for every field
+    /// appended after version 1.
+    fn upgrade(self) -> SafetyReport {
+        SafetyReport {
+            version: CURRENT_VERSION,
+            authority: self.authority,
+            token_mint: self.token_mint,
+            risk_score: self.risk_score,
+            risk_level: self.risk_level,
+            flags: self.flags,
+            flags_count: self.flags_count,
+            protocol_name: self.protocol_name,
+            report_uri: self.report_uri,
+            timestamp: self.timestamp,
+            created_at: self.created_at,
+            endorsements: self.endorsements,
+            disputes: self.disputes,
+            confidence: self.confidence,
+            min_tier: self.min_tier,
+            bump: self.bump,
+            resolved: false,
+            active: true,
+            category: CATEGORY_UNKNOWN,
+            finalized: false,
+            report_slot: 0,
+            cosigner: None,
+        }
+    }
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct Registry {
+    pub version: u8,          // Layout version, first so its offset never moves
+    pub authority: Pubkey,
+    pub total_reports: u64,
+    pub paused: bool,         // Halts submit_report/update_report when set
+    pub min_reputation: u16,  // Reporter reputation required to submit
+    pub max_report_age: i64,  // Seconds; 0 = reports never go stale
+    pub pending_authority: Option<Pubkey>,  // Set by propose_registry_authority
+    pub previous_authority: Option<Pubkey>, // Handed over from; its reports can be migrated here
+    pub bump: u8,
+    pub min_update_interval: i64, // Seconds between update_report calls per report; 0 = no limit
+    #[max_len(5)]
+    pub signers: Vec<Pubkey>, // Multisig members approving new reports
+    pub threshold: u8,        // Approvals required; 0 = single-authority submissions
+    pub flag_penalty_threshold: u8, // Flag count that caps the score; 0 = no check
+    pub max_score_with_flags: u8,   // Highest risk_score allowed at or above the threshold
+    pub safe_score_min: u8,   // get_verdict: lowest safe risk_score
+    pub safe_level_min: u8,   // get_verdict: lowest safe RiskLevel as u8 (High=0)
+}
+
+impl Registry {
+    /// Fails unless a HIGH report has a `cosigner` other than `authority` from
+    /// the registry's signer set. Any cosigner given must be in that set.
+    pub fn check_cosigner(&self, authority: Pubkey, risk_level: RiskLevel, cosigner: Option<Pubkey>) -> Result<()> {
+        match cosigner {
+            Some(key) => require!(key != authority && self.signers.contains(&key), ErrorCode::CosignRequired),
+            None => require!(risk_level != RiskLevel::High, ErrorCode::CosignRequired),
+        }
+        Ok(())
+    }
+
+    /// Fails if a report with `flags` claims a higher score than the registry
+    /// allows for that many flags.
+    pub fn check_consistency(&self, risk_score: u8, flags: u32) -> Result<()> {
+        if self.flag_penalty_threshold > 0 && flags.count_ones() >= self.flag_penalty_threshold as u32 {
+            require!(risk_score <= self.max_score_with_flags, ErrorCode::InconsistentRiskData);
+        }
+        Ok(())
+    }
+}
+
+/// Registry as v0.2.0 stored it, before `version`; read only by migrate_registry.
+#[derive(AnchorDeserialize, InitSpace)]
+pub struct LegacyRegistry {
+    pub authority: Pubkey,
+    pub total_reports: u64,
+    pub bump: u8,
+}
+
+impl LegacyRegistry {
+    fn into_v1(self) -> RegistryV1 {
+        RegistryV1 {
+            version: 0,
+            authority: self.authority,
+            total_reports: self.total_reports,
+            paused: false,
+            min_reputation: 0,
+            max_report_age: 0,
+            pending_authority: None,
+            previous_authority: None,
+            bump: self.bump,
+        }
+    }
+}
+
+/// The Registry fields of version 1, which every later version appends to;
+/// read only by migrate_registry.
+#[derive(AnchorDeserialize)]
+pub struct RegistryV1 {
+    pub version: u8,
+    pub authority: Pubkey,
+    pub total_reports: u64,
+    pub paused: bool,
+    pub min_reputation: u16,
+    pub max_report_age: i64,
+    pub pending_authority: Option<Pubkey>,
+    pub previous_authority: Option<Pubkey>,
+    pub bump: u8,
+}
+
+impl RegistryV1 {
+    /// The current Registry, with a new registry's defaults for every field
+    /// appended after version 1.
+    fn upgrade(self) -> Registry {
+        Registry {
+            version: CURRENT_VERSION,
+            authority: self.authority,
+            total_reports: self.total_reports,
+            paused: self.paused,
+            min_reputation: self.min_reputation,
+            max_report_age: self.max_report_age,
+            pending_authority: self.pending_authority,
+            previous_authority: self.previous_authority,
+            bump: self.bump,
+            min_update_interval: 0,
+            signers: Vec::new(),
+            threshold: 0,
+            flag_penalty_threshold: 0,
+            max_score_with_flags: 100,
+            safe_score_min: DEFAULT_SAFE_SCORE_MIN,
+            safe_level_min: DEFAULT_SAFE_LEVEL_MIN,
+        }
+    }
+}
+
+/// Running totals over every live report in every registry.
+#[account]
+#[derive(InitSpace)]
+pub struct GlobalStats {
+    pub total_reports: u64,
+    pub score_sum: u64,       // Sum of risk_score over live reports
+    pub high_reports: u64,
+    pub medium_reports: u64,
+    pub low_reports: u64,
+    pub bump: u8,
+}
+
+impl GlobalStats {
+    fn level_count(&mut self, level: RiskLevel) -> &mut u64 {
+        match level {
+            RiskLevel::High => &mut self.high_reports,
+            RiskLevel::Medium => &mut self.medium_reports,
+            RiskLevel::Low => &mut self.low_reports,
+        }
+    }
+
+    /// Counts a new report, or the new values of an updated one.
+    pub fn add(&mut self, score: u8, level: RiskLevel) -> Result<()> {
+        self.total_reports = self.total_reports.checked_add(1).ok_or(ErrorCode::ArithmeticOverflow)?;
+        self.score_sum = self.score_sum.checked_add(score as u64).ok_or(ErrorCode::ArithmeticOverflow)?;
+        let count = self.level_count(level);
+        *count = count.checked_add(1).ok_or(ErrorCode::ArithmeticOverflow)?;
+        Ok(())
+    }
+
+    /// Uncounts a closed report, or the old values of an updated one.
+    pub fn remove(&mut self, score: u8, level: RiskLevel) -> Result<()> {
+        self.total_reports = self.total_reports.checked_sub(1).ok_or(ErrorCode::ArithmeticOverflow)?;
+        self.score_sum = self.score_sum.checked_sub(score as u64).ok_or(ErrorCode::ArithmeticOverflow)?;
+        let count = self.level_count(level);
+        *count = count.checked_sub(1).ok_or(ErrorCode::ArithmeticOverflow)?;
+        Ok(())
+    }
+
+    /// Mean risk_score of live reports, rounded down; 0 if there are none.
+    pub fn average_score(&self) -> u8 {
+        self.score_sum.checked_div(self.total_reports).unwrap_or(0) as u8
+    }
+}
+
+/// Running totals over every live report for one token, across all authorities.
+#[account]
+#[derive(InitSpace)]
+pub struct TokenConsensus {
+    pub token_mint: Pubkey,
+    pub sum_scores: u64,      // Sum of risk_score over live reports
+    pub report_count: u64,
+    pub high_reports: u64,
+    pub medium_reports: u64,
+    pub low_reports: u64,
+    pub min_level: RiskLevel, // Riskiest level among live reports; Low when there are none
+    pub bump: u8,
+}
+
+impl TokenConsensus {
+    pub fn new(token_mint: Pubkey, bump: u8) -> Self {
+        Self {
+            token_mint,
+            sum_scores: 0,
+            report_count: 0,
+            high_reports: 0,
+            medium_reports: 0,
+            low_reports: 0,
+            min_level: RiskLevel::Low,
+            bump,
+        }
+    }
+
+    /// Fills in a freshly created (init_if_needed) consensus account.
+    pub fn init_if_new(&mut self, token_mint: Pubkey, bump: u8) {
+        if self.token_mint == Pubkey::default() {
+            *self = Self::new(token_mint, bump);
+        }
+    }
+
+    fn level_count(&mut self, level: RiskLevel) -> &mut u64 {
+        match level {
+            RiskLevel::High => &mut self.high_reports,
+            RiskLevel::Medium => &mut self.medium_reports,
+            RiskLevel::Low => &mut self.low_reports,
+        }
+    }
+
+    fn refresh_min_level(&mut self) {
+        self.min_level = if self.high_reports > 0 {
+            RiskLevel::High
+        } else if self.medium_reports > 0 {
+            RiskLevel::Medium
+        } else {
+            RiskLevel::Low
+        };
+    }
+
+    /// Counts a new report, or the new values of an updated one.
+    pub fn add(&mut self, score: u8, level: RiskLevel) -> Result<()> {
+        self.report_count = self.report_count.checked_add(1).ok_or(ErrorCode::ArithmeticOverflow)?;
+        self.sum_scores = self.sum_scores.checked_add(score as u64).ok_or(ErrorCode::ArithmeticOverflow)?;
+        let count = self.level_count(level);
+        *count = count.checked_add(1).ok_or(ErrorCode::ArithmeticOverflow)?;
+        self.refresh_min_level();
+        Ok(())
+    }
+
+    /// Uncounts a closed report, or the old values of an updated one.
+    pub fn remove(&mut self, score: u8, level: RiskLevel) -> Result<()> {
+        self.report_count = self.report_count.checked_sub(1).ok_or(ErrorCode::ArithmeticOverflow)?;
+        self.sum_scores = self.sum_scores.checked_sub(score as u64).ok_or(ErrorCode::ArithmeticOverflow)?;
+        let count = self.level_count(level);
+        *count = count.checked_sub(1).ok_or(ErrorCode::ArithmeticOverflow)?;
+        self.refresh_min_level();
+        Ok(())
+    }
+
+    /// Mean risk_score of live reports, rounded down; 0 if there are none.
+    pub fn average_score(&self) -> u8 {
+        self.sum_scores.checked_div(self.report_count).unwrap_or(0) as u8
+    }
+}
+
+/// A hot key allowed to submit reports for a registry authority.
+#[account]
+#[derive(InitSpace)]
+pub struct Delegate {
+    pub authority: Pubkey,
+    pub delegate: Pubkey,
+    pub revoked: bool,        // Set by revoke_delegate, cleared by add_delegate
+    pub created_at: i64,      // Last added
+    pub bump: u8,
+}
+
+/// A report proposed to a multisig registry, awaiting approvals.
+#[account]
+#[derive(InitSpace)]
+pub struct PendingReport {
+    pub registry: Pubkey,
+    pub proposer: Pubkey,
+    pub input: ReportInput,
+    #[max_len(5)]
+    pub approvals: Vec<Pubkey>, // Distinct members who approved, proposer first
+    pub created_at: i64,
+    pub bump: u8,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct Reporter {
+    pub authority: Pubkey,
+    pub reputation: u16,      // Weight in aggregate_report, 0-MAX_REPUTATION
+    pub reports_submitted: u64,
+    pub active_reports: u64,  // Submitted and not yet closed by close_report
+    pub reports_this_period: u64, // Counted against the tier quota
+    pub unsynced_reports: u64,    // submit_report_lite reports not yet in registry.total_reports
+    pub period_start: i64,
+    pub bump: u8,
+}
+
+impl Reporter {
+    /// Fills in a freshly created (init_if_needed) reporter profile.
+    pub fn init_if_new(&mut self, authority: Pubkey, bump: u8) {
+        if self.authority == Pubkey::default() {
+            self.authority = authority;
+            self.reputation = DEFAULT_REPUTATION;
+            self.bump = bump;
+        }
+    }
+
+    /// Counts `count` new reports against `quota`, starting a new period once
+    /// `period` seconds have passed since the current one began.
+    pub fn consume_quota(&mut self, count: u64, quota: u64, period: i64, now: i64) -> Result<()> {
+        if now.saturating_sub(self.period_start) >= period {
+            self.period_start = now;
+            self.reports_this_period = 0;
+        }
+        let used = self.reports_this_period.checked_add(count).ok_or(ErrorCode::ArithmeticOverflow)?;
+        require!(used <= quota, ErrorCode::ReportQuotaExceeded);
+        self.reports_this_period = used;
+        Ok(())
+    }
+}
+
+/// Progress of a recompute_total_reports count spread over several transactions.
+#[account]
+#[derive(InitSpace)]
+pub struct ReportRecount {
+    pub registry: Pubkey,
+    pub count: u64,           // Reports counted so far
+    pub last_report: Pubkey,  // Highest address counted; each page must continue above it
+    pub in_progress: bool,    // Set by the first page, cleared by the last
+    pub bump: u8,
+}
+
+/// Append-only log of a report's previous values, kept as a ring buffer of the
+/// last REPORT_HISTORY_LEN versions.
+#[account]
+#[derive(InitSpace)]
+pub struct ReportHistory {
+    pub report: Pubkey,
+    pub total_versions: u64,  // Entries ever recorded; also the latest version index
+    #[max_len(16)]
+    pub entries: Vec<HistoryEntry>,
+    pub bump: u8,
+}
+
+impl ReportHistory {
+    /// Records an entry, overwriting the oldest one once the ring is full.
+    /// The oldest entry lives at `total_versions % REPORT_HISTORY_LEN` when full.
+    /// Returns the new `total_versions`.
+    pub fn push(&mut self, entry: HistoryEntry) -> u64 {
+        let slot = (self.total_versions % REPORT_HISTORY_LEN as u64) as usize;
+        if self.entries.len() < REPORT_HISTORY_LEN {
+            self.entries.push(entry);
+        } else {
+            self.entries[slot] = entry;
+        }
+        self.total_versions = self.total_versions.saturating_add(1);
+        self.total_versions
+    }
+
+    /// Entries from oldest to newest, unwinding the ring buffer.
+    pub fn ordered(&self) -> impl Iterator<Item = &HistoryEntry> {
+        let start = if self.entries.len() < REPORT_HISTORY_LEN {
+            0
+        } else {
+            (self.total_versions % REPORT_HISTORY_LEN as u64) as usize
+        };
+        self.entries[start..].iter().chain(self.entries[..start].iter())
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, InitSpace)]
+pub struct HistoryEntry {
+    pub risk_score: u8,
+    pub risk_level: RiskLevel,
+    pub flags_count: u8,
+    pub timestamp: i64,
+}
+
+/// Short text reasons behind a report's flags, kept out of SafetyReport so the
+/// hot account stays small.
+#[account]
+#[derive(InitSpace)]
+pub struct ReportFlags {
+    pub report: Pubkey,
+    #[max_len(8, 48)]
+    pub reasons: Vec<String>, // MAX_FLAG_REASONS x MAX_FLAG_REASON_LEN bytes
+    pub bump: u8,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct Endorsement {
+    pub report: Pubkey,
+    pub endorser: Pubkey,
+    pub agree: bool,          // false = dispute
+    pub timestamp: i64,
+    pub bump: u8,
+}
+
+/// Token mints whose latest report carries a given flag bit.
+/// Capped at MAX_FLAG_INDEX_LEN; when full, the oldest entry is evicted (FIFO).
+#[account]
+#[derive(InitSpace)]
+pub struct FlagIndex {
+    pub flag_bit: u8,
+    #[max_len(64)]
+    pub token_mints: Vec<Pubkey>, // Oldest first
+    pub bump: u8,
+}
+
+impl FlagIndex {
+    /// Appends `mint` unless already present, evicting the oldest entry when full.
+    pub fn insert(&mut self, mint: Pubkey) {
+        if self.token_mints.contains(&mint) {
+            return;
+        }
+        if self.token_mints.len() >= MAX_FLAG_INDEX_LEN {
+            self.token_mints.remove(0);
+        }
+        self.token_mints.push(mint);
+    }
+
+    pub fn remove(&mut self, mint: &Pubkey) {
+        self.token_mints.retain(|m| m != mint);
+    }
+}
+
+/// Authorities with a live submit_report report on one token, so clients can
+/// derive every report PDA for the mint. Capped at MAX_TOKEN_INDEX_LEN; when
+/// full, the oldest entry is evicted (FIFO).
+#[account]
+#[derive(InitSpace)]
+pub struct TokenReportIndex {
+    pub token_mint: Pubkey,
+    #[max_len(32)]
+    pub authorities: Vec<Pubkey>, // Oldest first
+    #[max_len(32)]
+    pub slots: Vec<u8>,           // Bitmask of each authority's live report slots, parallel to `authorities`
+    pub bump: u8,
+}
+
+impl TokenReportIndex {
+    pub fn new(token_mint: Pubkey, bump: u8) -> Self {
+        Self {
+            token_mint,
+            authorities: Vec::new(),
+            slots: Vec::new(),
+            bump,
+        }
+    }
+
+    /// Fills in a freshly created (init_if_needed) index.
+    pub fn init_if_new(&mut self, token_mint: Pubkey, bump: u8) {
+        if self.token_mint == Pubkey::default() {
+            *self = Self::new(token_mint, bump);
+        }
+    }
+
+    /// Records `slot` for `authority`, appending the authority if it is not
+    /// listed yet and evicting the oldest entry when full.
+    pub fn insert(&mut self, authority: Pubkey, slot: u8) {
+        if let Some(i) = self.authorities.iter().position(|a| *a == authority) {
+            self.slots[i] |= 1 << slot;
+            return;
+        }
+        if self.authorities.len() >= MAX_TOKEN_INDEX_LEN {
+            self.authorities.remove(0);
+            self.slots.remove(0);
+        }
+        self.authorities.push(authority);
+        self.slots.push(1 << slot);
+    }
+
+    /// Clears `slot` for `authority`, dropping the authority once its last slot is gone.
+    pub fn remove(&mut self, authority: &Pubkey, slot: u8) {
+        if let Some(i) = self.authorities.iter().position(|a| a == authority) {
+            self.slots[i] &= !(1 << slot);
+            if self.slots[i] == 0 {
+                self.authorities.remove(i);
+                self.slots.remove(i);
+            }
+        }
+    }
+}
+
+// ============================================================================
+// Instruction Arguments
+// ============================================================================
+
+/// One report in a `submit_reports_batch` call, or a `submit_report_multisig` proposal.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
+pub struct ReportInput {
+    pub token_mint: Pubkey,
+    #[max_len(64)]
+    pub protocol_name: String,
+    pub risk_score: u8,
+    pub risk_level: RiskLevel,
+    pub flags: u32,
+    #[max_len(200)]
+    pub report_uri: String,
+    pub min_tier: u8,
+    pub category: u8,
+}
+
+// ============================================================================
+// Return Values
+// ============================================================================
+
+/// Result of `verify_subscription`.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct SubscriptionStatus {
+    pub verified: bool,
+    pub tier: u8,
+    pub expires_at: i64,
+}
+
+/// One entry of `get_token_reports`.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct TokenReporterView {
+    pub authority: Pubkey,
+    pub slots: u8, // Bit n set = report slot n exists
+}
+
+/// Result of `get_report`.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct ReportView {
+    pub token_mint: Pubkey,
+    pub risk_score: u8,
+    pub risk_level: RiskLevel,
+    pub flags: u32,
+    pub category: u8,
+    pub timestamp: i64,
+    pub protocol_name: String,
+}
+
+/// Result of `get_verdict`.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct VerdictView {
+    pub safe: bool,
+}
+
+/// Result of `get_global_stats`.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct GlobalStatsView {
+    pub total_reports: u64,
+    pub average_score: u8,
+    pub high_reports: u64,
+    pub medium_reports: u64,
+    pub low_reports: u64,
+}
+
+/// Result of `get_conventions`.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct ConventionsView {
+    pub score_convention: u8, // SCORE_HIGHER_IS_*
+    pub max_score: u8,
+}
+
+/// Result of `get_consensus`.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct ConsensusView {
+    pub token_mint: Pubkey,
+    pub report_count: u64,
+    pub average_score: u8,
+    pub min_level: RiskLevel,
+}
+
+/// One entry of `verify_subscriptions_batch`.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct SubscriptionSummary {
+    pub user: Pubkey,
+    pub tier: u8,     // After any due scheduled downgrade
+    pub active: bool, // Within expires_at + grace period
+}
+
+/// Result of `get_pricing`. Entry `i` of each vector is tier `i + 1`.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct PricingView {
+    pub tier_prices: Vec<u64>,    // Lamports per cycle
+    pub tier_durations: Vec<i64>, // Seconds per cycle
+    pub lifetime_price: u64,
+    pub promo_prices: Vec<u64>,   // Apply while now < promo_ends_at
+    pub promo_ends_at: i64,
+    pub grace_period: i64,
+    pub paused: bool,
+}
+
+// ============================================================================
+// Account Structs - Subscriptions
+// ============================================================================
+
+#[account]
+#[derive(InitSpace)]
+pub struct SubscriptionConfig {
+    pub version: u8,          // Layout version, first so its offset never moves
+    pub admin: Pubkey,
+    pub treasury: Pubkey,           // SOL treasury: receives lamport payments
+    #[max_len(8)]
+    pub tier_prices: Vec<u64>,      // Lamports, index 0 = tier 1
+    pub lifetime_price: u64,
+    #[max_len(8)]
+    pub tier_durations: Vec<i64>,   // seconds per cycle, index 0 = tier 1
+    pub tier_count: u8,             // Tiers with both a price and a duration
+    pub grace_period: i64,          // seconds of access after expires_at
+    pub total_subscribers: u64,
+    pub total_revenue: u64,
+    #[max_len(8)]
+    pub tier_subscribers: Vec<u64>, // Subscriptions started per tier; not decremented on cancel
+    #[max_len(8)]
+    pub tier_revenue: Vec<u64>,     // Lamports received per tier, before refunds
+    pub treasury_bump: u8,
+    pub pending_admin: Option<Pubkey>, // Set by propose_admin, cleared on accept/cancel
+    pub referral_bps: u16,          // Referrer share of referred subscriptions
+    pub trial_duration: i64,        // seconds
+    pub paused: bool,               // Freezes all payments, refunds and withdrawals
+    pub bump: u8,
+    pub refund_window: i64,         // seconds after created_at with a full refund
+    pub refund_mode: u8,            // REFUND_MODE_*
+    pub max_subscribers: u64,       // Cap on total_subscribers; 0 = unlimited
+    #[max_len(8)]
+    pub promo_prices: Vec<u64>,     // Replace tier_prices until promo_ends_at; may cover fewer tiers
+    pub promo_ends_at: i64,
+    #[max_len(9)]
+    pub report_quotas: Vec<u64>,    // Reports per period by tier, index 0 = unsubscribed; empty = no quotas
+    pub report_quota_period: i64,   // seconds
+    pub whitelist_root: [u8; 32],   // Merkle root for claim_whitelisted; zero = disabled
+    pub annual_discount_bps: u16,   // subscribe_annual discount off ANNUAL_CYCLES list-price cycles
+    pub last_admin_action: i64,     // Set by every admin instruction
+    pub inactivity_timeout: i64,    // seconds without admin action before auto_pause_if_stale; 0 = disabled
+    pub token_treasury: Pubkey,     // Owner of the token accounts subscribe_spl pays into
+    pub late_penalty_bps: u16,      // renew_subscription surcharge after a lapse
+    pub subscribe_cooldown: i64,    // seconds between a wallet's subscribes/cancels; 0 = no limit
+    pub revenue_split_set: bool,    // Set by set_revenue_split; payments must then pass the split
+}
+
+impl SubscriptionConfig {
+    /// Fails unless `tier` is between 1 and `tier_count`.
+    pub fn check_tier(&self, tier: u8) -> Result<()> {
+        require!(tier >= 1 && tier <= self.tier_count, ErrorCode::InvalidTier);
+        Ok(())
+    }
+
+    /// Marks the admin as active now, resetting the auto_pause_if_stale timer.
+    pub fn record_admin_action(&mut self) -> Result<()> {
+        self.last_admin_action = Clock::get()?.unix_timestamp;
+        Ok(())
+    }
+
+    /// Recomputes `tier_count` and grows the per-tier counters to match.
+    pub fn refresh_tier_count(&mut self) {
+        let count = self.tier_prices.len().min(self.tier_durations.len());
+        self.tier_count = count as u8;
+        if self.tier_subscribers.len() < count {
+            self.tier_subscribers.resize(count, 0);
+            self.tier_revenue.resize(count, 0);
+        }
+    }
+
+    /// SOL price of `tier` (1 = Basic, 2 = Pro, 3 = Alpha, ...).
+    pub fn tier_price(&self, tier: u8) -> Result<u64> {
+        self.check_tier(tier)?;
+        Ok(self.tier_prices[tier as usize - 1])
+    }
+
+    /// Price charged for `tier` at `now`: the promo price while a promo runs,
+    /// else the regular tier price.
+    pub fn current_price(&self, tier: u8, now: i64) -> Result<u64> {
+        let price = self.tier_price(tier)?;
+        match self.promo_prices.get(tier as usize - 1) {
+            Some(&promo) if now < self.promo_ends_at => Ok(promo.min(price)),
+            _ => Ok(price),
+        }
+    }
+
+    /// Reports a reporting authority with `subscription` may submit per
+    /// `report_quota_period`. Lapsed subscriptions count as tier 0.
+    pub fn report_quota(&self, subscription: Option<&Subscription>, now: i64) -> u64 {
+        if self.report_quotas.is_empty() {
+            return u64::MAX;
+        }
+        let tier = subscription
+            .filter(|s| s.expires_at.saturating_add(self.grace_period) > now)
+            .map_or(0, |s| s.current_tier(now));
+        self.report_quotas.get(tier as usize).copied().unwrap_or(u64::MAX)
+    }
+
+    /// subscribe_annual price of `tier`: ANNUAL_CYCLES list-price cycles less
+    /// `annual_discount_bps`, rounding down.
+    pub fn annual_price(&self, tier: u8) -> Result<u64> {
+        let full = (self.tier_price(tier)? as u128)
+            .checked_mul(ANNUAL_CYCLES as u128)
+            .ok_or(ErrorCode::ArithmeticOverflow)?;
+        let discounted = full * (10_000 - self.annual_discount_bps as u128) / 10_000;
+        u64::try_from(discounted).map_err(|_| error!(ErrorCode::ArithmeticOverflow))
+    }
+
+    /// `price * (10_000 + late_penalty_bps) / 10_000`, rounding down.
+    pub fn with_late_penalty(&self, price: u64) -> Result<u64> {
+        let total = (price as u128)
+            .checked_mul(10_000 + self.late_penalty_bps as u128)
+            .ok_or(ErrorCode::ArithmeticOverflow)?
+            / 10_000;
+        u64::try_from(total).map_err(|_| error!(ErrorCode::ArithmeticOverflow))
+    }
+
+    /// Cycle length of `tier` in seconds.
+    pub fn tier_duration(&self, tier: u8) -> Result<i64> {
+        self.check_tier(tier)?;
+        Ok(self.tier_durations[tier as usize - 1])
+    }
+
+    /// Counts a new subscriber at `tier` in the aggregate and per-tier counters.
+    /// Fails once `max_subscribers` is reached.
+    pub fn add_subscriber(&mut self, tier: u8) -> Result<()> {
+        self.check_tier(tier)?;
+        require!(
+            self.max_subscribers == 0 || self.total_subscribers < self.max_subscribers,
+            ErrorCode::SubscriberCapReached
+        );
+        let bucket = &mut self.tier_subscribers[tier as usize - 1];
+        *bucket = bucket.checked_add(1).ok_or(ErrorCode::ArithmeticOverflow)?;
+        self.total_subscribers = self.total_subscribers.checked_add(1).ok_or(ErrorCode::ArithmeticOverflow)?;
+        Ok(())
+    }
+
+    /// Records `amount` lamports of revenue for `tier` in the aggregate and per-tier counters.
+    pub fn add_revenue(&mut self, tier: u8, amount: u64) -> Result<()> {
+        self.check_tier(tier)?;
+        let bucket = &mut self.tier_revenue[tier as usize - 1];
+        *bucket = bucket.checked_add(amount).ok_or(ErrorCode::ArithmeticOverflow)?;
+        self.total_revenue = self.total_revenue.checked_add(amount).ok_or(ErrorCode::ArithmeticOverflow)?;
+        Ok(())
+    }
+}
+
+/// SubscriptionConfig as v0.2.0 stored it, before `version`; read only by
+/// migrate_subscription_config.
+#[derive(AnchorDeserialize, InitSpace)]
+pub struct LegacySubscriptionConfig {
+    pub admin: Pubkey,
+    pub treasury: Pubkey,
+    pub basic_price: u64,
+    pub pro_price: u64,
+    pub alpha_price: u64,
+    pub subscription_duration: i64, // One cycle length for every tier
+    pub total_subscribers: u64,
+    pub total_revenue: u64,
+    pub bump: u8,
+}
+
+impl LegacySubscriptionConfig {
+    /// The config as version 1 would hold it. v0.2.0 had no lifetime plan, so
+    /// its price is `u64::MAX` until update_pricing sets one, and no per-tier
+    /// counters, so those start at zero.
+    fn into_v1(self, treasury_bump: u8) -> SubscriptionConfigV1 {
+        SubscriptionConfigV1 {
+            version: 0,
+            admin: self.admin,
+            treasury: self.treasury,
+            basic_price: self.basic_price,
+            pro_price: self.pro_price,
+            alpha_price: self.alpha_price,
+            lifetime_price: u64::MAX,
+            basic_duration: self.subscription_duration,
+            pro_duration: self.subscription_duration,
+            alpha_duration: self.subscription_duration,
+            grace_period: 0,
+            total_subscribers: self.total_subscribers,
+            total_revenue: self.total_revenue,
+            basic_subscribers: 0,
+            pro_subscribers: 0,
+            alpha_subscribers: 0,
+            basic_revenue: 0,
+            pro_revenue: 0,
+            alpha_revenue: 0,
+            treasury_bump,
+            pending_admin: None,
+            referral_bps: 0,
+            trial_duration: DEFAULT_TRIAL_DURATION,
+            paused: false,
+            bump: self.bump,
+        }
+    }
+}
+
+/// The SubscriptionConfig fields of versions 1 and 2, before version 3
+/// replaced the basic/pro/alpha fields; read only by migrate_subscription_config.
+#[derive(AnchorDeserialize)]
+pub struct SubscriptionConfigV1 {
+    pub version: u8,
+    pub admin: Pubkey,
+    pub treasury: Pubkey,
+    pub basic_price: u64,
+    pub pro_price: u64,
+    pub alpha_price: u64,
+    pub lifetime_price: u64,
+    pub basic_duration: i64,
+    pub pro_duration: i64,
+    pub alpha_duration: i64,
+    pub grace_period: i64,
+    pub total_subscribers: u64,
+    pub total_revenue: u64,
+    pub basic_subscribers: u64,
+    pub pro_subscribers: u64,
+    pub alpha_subscribers: u64,
+    pub basic_revenue: u64,
+    pub pro_revenue: u64,
+    pub alpha_revenue: u64,
+    pub treasury_bump: u8,
+    pub pending_admin: Option<Pubkey>,
+    pub referral_bps: u16,
+    pub trial_duration: i64,
+    pub paused: bool,
+    pub bump: u8,
+}
+
+impl SubscriptionConfigV1 {
+    /// The version 3 layout, with basic/pro/alpha as tiers 1 to 3.
+    fn into_v3(self) -> SubscriptionConfigV3 {
+        SubscriptionConfigV3 {
+            version: self.version,
+            admin: self.admin,
+            treasury: self.treasury,
+            tier_prices: vec![self.basic_price, self.pro_price, self.alpha_price],
+            lifetime_price: self.lifetime_price,
+            tier_durations: vec![self.basic_duration, self.pro_duration, self.alpha_duration],
+            tier_count: 3,
+            grace_period: self.grace_period,
+            total_subscribers: self.total_subscribers,
+            total_revenue: self.total_revenue,
+            tier_subscribers: vec![self.basic_subscribers, self.pro_subscribers, self.alpha_subscribers],
+            tier_revenue: vec![self.basic_revenue, self.pro_revenue, self.alpha_revenue],
+            treasury_bump: self.treasury_bump,
+            pending_admin: self.pending_admin,
+            referral_bps: self.referral_bps,
+            trial_duration: self.trial_duration,
+            paused: self.paused,
+            bump: self.bump,
+        }
+    }
+}
+
+/// The SubscriptionConfig fields of version 3, which every later version
+/// appends to; read only by migrate_subscription_config.
+#[derive(AnchorDeserialize)]
+pub struct SubscriptionConfigV3 {
+    pub version: u8,
+    pub admin: Pubkey,
+    pub treasury: Pubkey,
+    pub tier_prices: Vec<u64>,
+    pub lifetime_price: u64,
+    pub tier_durations: Vec<i64>,
+    pub tier_count: u8,
+    pub grace_period: i64,
+    pub total_subscribers: u64,
+    pub total_revenue: u64,
+    pub tier_subscribers: Vec<u64>,
+    pub tier_revenue: Vec<u64>,
+    pub treasury_bump: u8,
+    pub pending_admin: Option<Pubkey>,
+    pub referral_bps: u16,
+    pub trial_duration: i64,
+    pub paused: bool,
+    pub bump: u8,
+}
+
+impl SubscriptionConfigV3 {
+    /// The current SubscriptionConfig, with initialize_subscription_config's
+    /// defaults for every field appended after version 3.
+    fn upgrade(self) -> SubscriptionConfig {
+        SubscriptionConfig {
+            version: CURRENT_VERSION,
+            admin: self.admin,
+            treasury: self.treasury,
+            tier_prices: self.tier_prices,
+            lifetime_price: self.lifetime_price,
+            tier_durations: self.tier_durations,
+            tier_count: self.tier_count,
+            grace_period: self.grace_period,
+            total_subscribers: self.total_subscribers,
+            total_revenue: self.total_revenue,
+            tier_subscribers: self.tier_subscribers,
+            tier_revenue: self.tier_revenue,
+            treasury_bump: self.treasury_bump,
+            pending_admin: self.pending_admin,
+            referral_bps: self.referral_bps,
+            trial_duration: self.trial_duration,
+            paused: self.paused,
+            bump: self.bump,
+            refund_window: 0,
+            refund_mode: REFUND_MODE_PRORATED,
+            max_subscribers: 0,
+            promo_prices: Vec::new(),
+            promo_ends_at: 0,
+            report_quotas: Vec::new(),
+            report_quota_period: DEFAULT_REPORT_QUOTA_PERIOD,
+            whitelist_root: [0; 32],
+            annual_discount_bps: 0,
+            last_admin_action: 0,
+            inactivity_timeout: 0,
+            token_treasury: self.treasury,
+            late_penalty_bps: 0,
+            subscribe_cooldown: 0,
+            revenue_split_set: false,
+        }
+    }
+}
 
-    /// CHECK: Treasury to receive payment.
-    #[account(
-        mut,
-        constraint = treasury.key() == subscription_config.treasury @ ErrorCode::InvalidTreasury
-    )]
-    pub treasury: AccountInfo<'info>,
+/// A user's purchases, renewals and cancellations, kept as a ring buffer of
+/// the last SUBSCRIPTION_HISTORY_LEN entries; once full, each new entry
+/// overwrites the oldest.
+#[account]
+#[derive(InitSpace)]
+pub struct SubscriptionHistory {
+    pub user: Pubkey,
+    pub total_entries: u64,   // Entries ever recorded
+    #[max_len(16)]
+    pub entries: Vec<SubscriptionHistoryEntry>,
+    pub bump: u8,
+}
 
-    #[account(mut)]
-    pub user: Signer<'info>,
+impl SubscriptionHistory {
+    /// Records an entry for `user`, initializing a fresh account and
+    /// overwriting the oldest entry once the ring is full.
+    /// The oldest entry lives at `total_entries % SUBSCRIPTION_HISTORY_LEN` when full.
+    pub fn record(&mut self, user: Pubkey, bump: u8, entry: SubscriptionHistoryEntry) {
+        if self.user == Pubkey::default() {
+            self.user = user;
+            self.bump = bump;
+        }
+        let slot = (self.total_entries % SUBSCRIPTION_HISTORY_LEN as u64) as usize;
+        if self.entries.len() < SUBSCRIPTION_HISTORY_LEN {
+            self.entries.push(entry);
+        } else {
+            self.entries[slot] = entry;
+        }
+        self.total_entries = self.total_entries.saturating_add(1);
+    }
 
-    pub system_program: Program<'info, System>,
+    /// Entries from oldest to newest, unwinding the ring buffer.
+    pub fn ordered(&self) -> impl Iterator<Item = &SubscriptionHistoryEntry> {
+        let start = if self.entries.len() < SUBSCRIPTION_HISTORY_LEN {
+            0
+        } else {
+            (self.total_entries % SUBSCRIPTION_HISTORY_LEN as u64) as usize
+        };
+        self.entries[start..].iter().chain(self.entries[..start].iter())
+    }
 }
 
-#[derive(Accounts)]
-pub struct VerifySubscription<'info> {
-    #[account(
-        seeds = [b"subscription", subscription.user.as_ref()],
-        bump = subscription.bump
-    )]
-    pub subscription: Account<'info, Subscription>,
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, InitSpace)]
+pub struct SubscriptionHistoryEntry {
+    pub action: u8,           // SUB_ACTION_*
+    pub tier: u8,
+    pub price: u64,           // Lamports charged, or refunded for a cancel
+    pub timestamp: i64,
 }
 
-#[derive(Accounts)]
-pub struct UpdatePricing<'info> {
-    #[account(
-        mut,
-        seeds = [b"subscription_config"],
-        bump = subscription_config.bump,
-        has_one = admin @ ErrorCode::Unauthorized
-    )]
-    pub subscription_config: Account<'info, SubscriptionConfig>,
+#[account]
+#[derive(InitSpace)]
+pub struct Subscription {
+    pub version: u8,          // Layout version, first so its offset never moves
+    pub user: Pubkey,
+    pub tier: u8,             // 1=Basic, 2=Pro, 3=Alpha
+    pub expires_at: i64,
+    pub created_at: i64,
+    pub total_paid: u64,      // Lamports
+    pub auto_renew: bool,     // Renewable by crank_renew from the user's escrow
+    pub is_trial: bool,       // Free trial not yet converted to a paid subscription
+    pub pending_tier: u8,     // Scheduled downgrade; 0 = none
+    pub pending_tier_at: i64, // When pending_tier takes effect
+    pub lifetime: bool,       // Permanent access; expires_at = i64::MAX
+    pub disabled: bool,       // User has churned (disable_subscription); cleared on renewal
+    pub bump: u8,
+    pub receipt_mint: Pubkey, // Receipt NFT from mint_receipt; default = none
+    pub first_subscribed_at: i64,  // First paid or granted period; 0 = never
+    pub total_active_seconds: i64, // Active time accrued up to active_since
+    pub active_since: i64,         // Start of the span not yet in total_active_seconds
+}
 
-    pub admin: Signer<'info>,
+impl Subscription {
+    /// True for a freshly created account or an unconverted trial, i.e. one
+    /// that `subscribe` may (over)write.
+    pub fn is_new_or_trial(&self) -> bool {
+        self.user == Pubkey::default() || self.is_trial
+    }
+
+    fn is_active_paid(&self, now: i64) -> bool {
+        !self.is_trial && self.tier != 0 && self.expires_at > now
+    }
+
+    /// Lamports due to renew at `tier` now, and the credit already deducted
+    /// from the tier price (the unused value of the current tier on a mid-cycle upgrade).
+    pub fn renewal_charge(&self, config: &SubscriptionConfig, tier: u8, now: i64) -> Result<(u64, u64)> {
+        let price = config.current_price(tier, now)?;
+        let credit = if self.is_active_paid(now) && tier > self.tier {
+            prorated_amount(
+                config.current_price(self.tier, now)?,
+                self.expires_at.saturating_sub(now),
+                config.tier_duration(self.tier)?,
+            )
+        } else {
+            0
+        };
+        Ok((price.saturating_sub(credit), credit))
+    }
+
+    /// Applies a renewal at `tier` paid with `charge` lamports and returns the new expiry.
+    /// Upgrades apply immediately and start a fresh cycle now, since the remaining
+    /// time was credited; downgrades are scheduled for the current expiry; trial
+    /// conversions and lapsed subscriptions start now; other renewals stack on
+    /// the current expiry.
+    pub fn apply_renewal(&mut self, config: &SubscriptionConfig, tier: u8, charge: u64, now: i64) -> Result<i64> {
+        self.accrue_active_time(now)?;
+        let active_paid = self.is_active_paid(now);
+        let is_upgrade = active_paid && tier > self.tier;
+        let is_downgrade = active_paid && tier < self.tier;
+
+        let base_time = if active_paid && !is_upgrade { self.expires_at } else { now };
+        let new_expiry = base_time.checked_add(config.tier_duration(tier)?).ok_or(ErrorCode::ArithmeticOverflow)?;
+
+        if is_downgrade {
+            self.pending_tier = tier;
+            self.pending_tier_at = self.expires_at;
+        } else {
+            self.tier = tier;
+            self.pending_tier = 0;
+            self.pending_tier_at = 0;
+        }
+        self.expires_at = new_expiry;
+        self.total_paid = self.total_paid.checked_add(charge).ok_or(ErrorCode::ArithmeticOverflow)?;
+        self.is_trial = false;
+        self.disabled = false;
+        Ok(new_expiry)
+    }
+
+    /// Adds the active part of the span since `active_since`
+    /// (`min(now, expires_at) - active_since`) to `total_active_seconds` and
+    /// starts a new span at `now`. Call before extending `expires_at`.
+    pub fn accrue_active_time(&mut self, now: i64) -> Result<()> {
+        if self.first_subscribed_at == 0 {
+            self.first_subscribed_at = now;
+        } else {
+            let active = now.min(self.expires_at).saturating_sub(self.active_since).max(0);
+            self.total_active_seconds = self.total_active_seconds.checked_add(active).ok_or(ErrorCode::ArithmeticOverflow)?;
+        }
+        self.active_since = now;
+        Ok(())
+    }
+
+    /// Total active time at `now`, including the current unaccrued span.
+    pub fn loyalty_seconds(&self, now: i64) -> i64 {
+        if self.first_subscribed_at == 0 {
+            return 0;
+        }
+        let current = now.min(self.expires_at).saturating_sub(self.active_since).max(0);
+        self.total_active_seconds.saturating_add(current)
+    }
+
+    /// The tier in effect at `now`, counting a due scheduled downgrade.
+    pub fn current_tier(&self, now: i64) -> u8 {
+        if self.pending_tier != 0 && now >= self.pending_tier_at {
+            self.pending_tier
+        } else {
+            self.tier
+        }
+    }
+
+    /// Switches to `pending_tier` once `pending_tier_at` has passed.
+    pub fn apply_pending_tier(&mut self, now: i64) {
+        if self.pending_tier != 0 && now >= self.pending_tier_at {
+            self.tier = self.pending_tier;
+            self.pending_tier = 0;
+            self.pending_tier_at = 0;
+        }
+    }
 }
 
-// ============================================================================
-// Account Structs - Registry
-// ============================================================================
+/// Subscription as v0.2.0 stored it, before `version`; read only by
+/// migrate_subscription.
+#[derive(AnchorDeserialize, InitSpace)]
+pub struct LegacySubscription {
+    pub user: Pubkey,
+    pub tier: u8,
+    pub expires_at: i64,
+    pub created_at: i64,
+    pub total_paid: u64,
+    pub bump: u8,
+}
+
+impl LegacySubscription {
+    fn into_v1(self) -> SubscriptionV1 {
+        SubscriptionV1 {
+            version: 0,
+            user: self.user,
+            tier: self.tier,
+            expires_at: self.expires_at,
+            created_at: self.created_at,
+            total_paid: self.total_paid,
+            auto_renew: false,
+            is_trial: false,
+            pending_tier: 0,
+            pending_tier_at: 0,
+            lifetime: false,
+            disabled: false,
+            bump: self.bump,
+        }
+    }
+}
+
+/// The Subscription fields of version 1, which every later version appends
+/// to; read only by migrate_subscription.
+#[derive(AnchorDeserialize)]
+pub struct SubscriptionV1 {
+    pub version: u8,
+    pub user: Pubkey,
+    pub tier: u8,
+    pub expires_at: i64,
+    pub created_at: i64,
+    pub total_paid: u64,
+    pub auto_renew: bool,
+    pub is_trial: bool,
+    pub pending_tier: u8,
+    pub pending_tier_at: i64,
+    pub lifetime: bool,
+    pub disabled: bool,
+    pub bump: u8,
+}
 
+impl SubscriptionV1 {
+    /// The current Subscription with no receipt, and tenure counted from
+    /// `created_at` for a paid subscription, as tenure tracking did not exist yet.
+    fn upgrade(self) -> Subscription {
+        let paid = !self.is_trial && self.tier != 0;
+        Subscription {
+            version: CURRENT_VERSION,
+            user: self.user,
+            tier: self.tier,
+            expires_at: self.expires_at,
+            created_at: self.created_at,
+            total_paid: self.total_paid,
+            auto_renew: self.auto_renew,
+            is_trial: self.is_trial,
+            pending_tier: self.pending_tier,
+            pending_tier_at: self.pending_tier_at,
+            lifetime: self.lifetime,
+            disabled: self.disabled,
+            bump: self.bump,
+            receipt_mint: Pubkey::default(),
+            first_subscribed_at: if paid { self.created_at } else { 0 },
+            total_active_seconds: 0,
+            active_since: if paid { self.created_at } else { 0 },
+        }
+    }
+}
+
+/// Per-wallet rate-limit state for subscribe and cancel, PDA `[subscriber_meta, user]`.
 #[account]
 #[derive(InitSpace)]
-pub struct SafetyReport {
-    pub authority: Pubkey,
-    pub token_mint: Pubkey,
-    pub risk_score: u8,       // 0-100 (higher = safer)
-    pub risk_level: u8,       // 0=HIGH, 1=MEDIUM, 2=LOW
-    pub flags_count: u8,
-    #[max_len(32)]
-    pub protocol_name: String,
-    pub timestamp: i64,
+pub struct SubscriberMeta {
+    pub user: Pubkey,
+    pub last_subscribe_at: i64, // Last subscribe or cancel; 0 = none
     pub bump: u8,
 }
 
+impl SubscriberMeta {
+    /// Fails with ActionRateLimited within `cooldown` seconds of the wallet's
+    /// last subscribe or cancel, then records `now`. Initializes a fresh account.
+    pub fn record_action(&mut self, user: Pubkey, bump: u8, cooldown: i64, now: i64) -> Result<()> {
+        if self.user == Pubkey::default() {
+            self.user = user;
+            self.bump = bump;
+        } else {
+            require!(
+                now.saturating_sub(self.last_subscribe_at) >= cooldown,
+                ErrorCode::ActionRateLimited
+            );
+        }
+        self.last_subscribe_at = now;
+        Ok(())
+    }
+}
+
 #[account]
 #[derive(InitSpace)]
-pub struct Registry {
-    pub authority: Pubkey,
-    pub total_reports: u64,
+pub struct TrialRecord {
+    pub user: Pubkey,
+    pub started_at: i64,
     pub bump: u8,
 }
 
-// ============================================================================
-// Account Structs - Subscriptions
-// ============================================================================
+/// Program-owned; holds `balance` lamports for crank_renew on top of its rent.
+#[account]
+#[derive(InitSpace)]
+pub struct Escrow {
+    pub user: Pubkey,
+    pub balance: u64,         // Lamports available for renewals, excluding rent
+    pub bump: u8,
+}
 
 #[account]
 #[derive(InitSpace)]
-pub struct SubscriptionConfig {
-    pub admin: Pubkey,
-    pub treasury: Pubkey,
-    pub basic_price: u64,     // Lamports
-    pub pro_price: u64,
-    pub alpha_price: u64,
-    pub subscription_duration: i64, // seconds
-    pub total_subscribers: u64,
+pub struct AcceptedMint {
+    pub mint: Pubkey,
+    #[max_len(8)]
+    pub tier_prices: Vec<u64>, // Base units of `mint`, index 0 = tier 1
     pub total_revenue: u64,
     pub bump: u8,
 }
 
+impl AcceptedMint {
+    /// Price of `tier` in this mint; tiers it does not price are rejected.
+    pub fn tier_price(&self, tier: u8) -> Result<u64> {
+        let index = (tier as usize).checked_sub(1).ok_or(ErrorCode::InvalidTier)?;
+        Ok(*self.tier_prices.get(index).ok_or(ErrorCode::InvalidTier)?)
+    }
+}
+
+/// Running audit trail of admin refunds to one user.
 #[account]
 #[derive(InitSpace)]
-pub struct Subscription {
+pub struct RefundRecord {
     pub user: Pubkey,
-    pub tier: u8,             // 1=Basic, 2=Pro, 3=Alpha
+    pub total_refunded: u64,  // Lamports
+    pub refund_count: u32,
+    pub last_amount: u64,
+    pub last_refund_at: i64,
+    pub bump: u8,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct Coupon {
+    pub code_hash: [u8; 32],
+    pub discount_bps: u16,    // Off the tier price in subscribe
+    pub max_uses: u32,
+    pub uses: u32,
     pub expires_at: i64,
-    pub created_at: i64,
-    pub total_paid: u64,
     pub bump: u8,
 }
 
+#[account]
+#[derive(InitSpace)]
+pub struct Whitelist {
+    pub user: Pubkey,
+    pub tier: u8,             // Granted by claim_free_subscription
+    pub claimed: bool,
+    pub added_at: i64,
+    pub bump: u8,
+}
+
+/// Token mints a user follows. Off-chain services match WatchedTokenUpdated
+/// events against these to push alerts.
+#[account]
+#[derive(InitSpace)]
+pub struct Watchlist {
+    pub user: Pubkey,
+    #[max_len(32)]
+    pub token_mints: Vec<Pubkey>,
+    pub bump: u8,
+}
+
+/// Marks a wallet's claim_whitelisted claim under one whitelist root.
+#[account]
+#[derive(InitSpace)]
+pub struct MerkleClaim {
+    pub user: Pubkey,
+    pub root: [u8; 32],
+    pub tier: u8,
+    pub claimed_at: i64,      // 0 until claimed
+    pub bump: u8,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct RevenueSplit {
+    #[max_len(4)]
+    pub entries: Vec<SplitEntry>, // bps sum to 10_000
+    pub bump: u8,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
+pub struct SplitEntry {
+    pub recipient: Pubkey,
+    pub bps: u16,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct Referral {
+    pub referrer: Pubkey,
+    pub total_referred: u64,
+    pub total_earned: u64,    // Lamports
+    pub bump: u8,
+}
+
+// ============================================================================
+// Events
+// ============================================================================
+
+#[event]
+pub struct ReportSubmitted {
+    pub report: Pubkey,
+    pub token_mint: Pubkey,
+    pub authority: Pubkey,
+    pub risk_score: u8,
+    pub risk_level: RiskLevel,
+    pub flags: u32,
+    pub flags_count: u8,
+    pub category: u8,
+    pub created_at: i64,
+    pub timestamp: i64,       // Last updated
+}
+
+/// Emitted by finalize_report with the report's frozen values.
+#[event]
+pub struct ReportFinalized {
+    pub report: Pubkey,
+    pub token_mint: Pubkey,
+    pub authority: Pubkey,
+    pub risk_score: u8,
+    pub risk_level: RiskLevel,
+    pub flags: u32,
+    pub finalized_at: i64,
+}
+
+/// Emitted by recompute_total_reports with the counter before and after the reset.
+#[event]
+pub struct TotalReportsRecomputed {
+    pub registry: Pubkey,
+    pub before: u64,
+    pub after: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ReportUpdated {
+    pub report: Pubkey,
+    pub token_mint: Pubkey,
+    pub authority: Pubkey,
+    pub old_score: u8,
+    pub new_score: u8,
+    pub category: u8,
+    pub created_at: i64,
+    pub timestamp: i64,       // Last updated
+}
+
+/// Emitted by report updates only when the score changes; negative = less safe.
+#[event]
+pub struct RiskChanged {
+    pub token_mint: Pubkey,
+    pub delta: i16,
+    pub new_level: RiskLevel, // Serialized as one byte, like a u8
+}
+
+#[event]
+pub struct DisputeResolved {
+    pub report: Pubkey,
+    pub token_mint: Pubkey,
+    pub risk_score: u8,
+    pub risk_level: RiskLevel,
+    pub flags: u32,
+    pub endorsements: u32,
+    pub disputes: u32,
+}
+
+#[event]
+pub struct RiskTrend {
+    pub report: Pubkey,
+    pub token_mint: Pubkey,
+    pub scores: Vec<u8>,      // Oldest first; the last entry is the current score
+    pub delta: i16,           // Last score minus first
+    pub improving: bool,      // Higher scores are safer
+}
+
+#[event]
+pub struct SubscriptionPurchased {
+    pub subscription: Pubkey,
+    pub user: Pubkey,
+    pub tier: u8,
+    pub price: u64,           // Lamports, including any referral share
+    pub expires_at: i64,
+    pub created_at: i64,
+}
+
+#[event]
+pub struct SubscriptionRenewed {
+    pub subscription: Pubkey,
+    pub user: Pubkey,
+    pub old_tier: u8,         // 0 if the subscription was cancelled
+    pub new_tier: u8,
+    pub price: u64,           // Lamports charged, after any upgrade credit
+    pub expires_at: i64,
+    pub late_penalty: bool,   // Renewed after a lapse; price includes late_penalty_bps
+}
+
+/// Emitted by revoke_subscription.
+#[event]
+pub struct SubscriptionRevoked {
+    pub subscription: Pubkey,
+    pub user: Pubkey,
+    pub tier: u8,             // Tier at revocation
+    pub expires_at: i64,      // Expiry before revocation
+    pub revoked_at: i64,
+}
+
+#[event]
+pub struct SubscriptionGifted {
+    pub subscription: Pubkey,
+    pub gifter: Pubkey,
+    pub recipient: Pubkey,
+    pub tier: u8,
+    pub price: u64,           // Lamports charged to the gifter
+    pub expires_at: i64,
+}
+
+#[event]
+pub struct SubscriptionsPauseChanged {
+    pub paused: bool,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct TreasuryUpdated {
+    pub old_treasury: Pubkey,
+    pub new_treasury: Pubkey,
+    pub timestamp: i64,
+}
+
+/// Emitted by every report update, keyed by mint for watchlist alerting.
+#[event]
+pub struct WatchedTokenUpdated {
+    pub token_mint: Pubkey,
+    pub report: Pubkey,
+    pub risk_score: u8,
+    pub risk_level: RiskLevel,
+    pub timestamp: i64,
+}
+
+/// Emitted by get_conventions.
+#[event]
+pub struct ScoreConvention {
+    pub score_convention: u8, // SCORE_HIGHER_IS_*
+    pub max_score: u8,
+}
+
+/// Emitted for every SOL payment of subscription revenue, so revenue can be
+/// indexed from one event type. With a revenue split, `amount` is the total
+/// paid across its recipients.
+#[event]
+pub struct TreasuryInflow {
+    pub source: Pubkey,       // Wallet the payment came from
+    pub amount: u64,          // Lamports, net of any referral cut
+    pub reason: u8,           // INFLOW_*
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct TreasurySwept {
+    pub old_treasury: Pubkey,
+    pub new_treasury: Pubkey,
+    pub amount: u64,          // Lamports moved from the treasury PDA
+    pub timestamp: i64,
+}
+
 // ============================================================================
 // Error Codes
 // ============================================================================
@@ -475,7 +8155,7 @@ pub enum ErrorCode {
     InvalidRiskScore,
     #[msg("Risk level must be 0 (HIGH), 1 (MEDIUM), or 2 (LOW)")]
     InvalidRiskLevel,
-    #[msg("Protocol name must be 32 characters or less")]
+    #[msg("Protocol name must be 64 bytes or less")]
     ProtocolNameTooLong,
     #[msg("Invalid subscription tier (must be 1-3)")]
     InvalidTier,
@@ -485,4 +8165,320 @@ pub enum ErrorCode {
     InsufficientSubscription,
     #[msg("Unauthorized")]
     Unauthorized,
+    #[msg("Token account mint does not match the payment mint")]
+    InvalidPaymentMint,
+    #[msg("Withdrawal would leave the treasury below rent exemption")]
+    InsufficientTreasuryBalance,
+    #[msg("No admin transfer is pending")]
+    NoPendingAdmin,
+    #[msg("Subscription has already been cancelled")]
+    SubscriptionAlreadyCancelled,
+    #[msg("Report URI must be 200 characters or less")]
+    ReportUriTooLong,
+    #[msg("Flags bitmask sets reserved bits")]
+    InvalidFlags,
+    #[msg("Grace period cannot be negative")]
+    InvalidGracePeriod,
+    #[msg("Referral share must be at most 10000 bps")]
+    InvalidReferralBps,
+    #[msg("Cannot refer yourself")]
+    SelfReferral,
+    #[msg("Referral account or wallet does not match the referrer")]
+    InvalidReferral,
+    #[msg("Reputation exceeds the maximum")]
+    ReputationTooHigh,
+    #[msg("Expected [SafetyReport, Reporter] account pairs")]
+    InvalidRemainingAccounts,
+    #[msg("Report is for a different token mint")]
+    TokenMintMismatch,
+    #[msg("Reporter account does not belong to the report authority")]
+    ReporterMismatch,
+    #[msg("Total reputation weight is zero")]
+    ZeroTotalWeight,
+    #[msg("Registry is paused")]
+    RegistryPaused,
+    #[msg("Batch must contain between 1 and 10 reports")]
+    InvalidBatchSize,
+    #[msg("Report account does not match the expected PDA")]
+    InvalidReportAccount,
+    #[msg("A report already exists for this token and authority")]
+    ReportAlreadyExists,
+    #[msg("Auto-renew is not enabled for this subscription")]
+    AutoRenewDisabled,
+    #[msg("Subscription has not expired yet")]
+    SubscriptionNotExpired,
+    #[msg("Flag index account does not match the flag bit")]
+    FlagIndexMismatch,
+    #[msg("Subscription already exists; use renew_subscription")]
+    SubscriptionAlreadyExists,
+    #[msg("Trial duration must be positive")]
+    InvalidTrialDuration,
+    #[msg("Lifetime subscriptions cannot be renewed or cancelled")]
+    LifetimeSubscription,
+    #[msg("Treasury cannot be the paying user")]
+    TreasuryIsUser,
+    #[msg("Authorities cannot endorse their own reports")]
+    SelfEndorsement,
+    #[msg("Reporter reputation is below the registry minimum")]
+    InsufficientReputation,
+    #[msg("Subscriptions and payments are paused")]
+    SubscriptionsPaused,
+    #[msg("Subscription durations must be positive")]
+    InvalidDuration,
+    #[msg("Recipient cannot be the default pubkey")]
+    InvalidRecipient,
+    #[msg("Subscription is still active or within its grace period")]
+    SubscriptionStillActive,
+    #[msg("Discount cannot exceed 10000 basis points")]
+    InvalidDiscount,
+    #[msg("Coupon has expired")]
+    CouponExpired,
+    #[msg("Coupon has reached its maximum uses")]
+    CouponExhausted,
+    #[msg("Cannot transfer a subscription to the same wallet")]
+    SelfTransfer,
+    #[msg("At most 8 flag reasons per report")]
+    TooManyFlagReasons,
+    #[msg("Flag reason too long (max 48 bytes)")]
+    FlagReasonTooLong,
+    #[msg("Price exceeds the caller's max_price")]
+    PriceExceedsMax,
+    #[msg("Max report age cannot be negative")]
+    InvalidMaxReportAge,
+    #[msg("Report is older than the registry's max report age")]
+    ReportStale,
+    #[msg("Refund exceeds what the subscription has paid")]
+    RefundExceedsPaid,
+    #[msg("Revenue split needs 1 to 4 recipients")]
+    InvalidSplitCount,
+    #[msg("Revenue split bps must sum to 10000")]
+    InvalidSplitBps,
+    #[msg("Recipient does not match the revenue split")]
+    InvalidSplitRecipient,
+    #[msg("Tier prices must satisfy basic <= pro <= alpha")]
+    PricingOrderInvalid,
+    #[msg("Tier price is below the minimum")]
+    PriceBelowFloor,
+    #[msg("Whitelist entry has already been claimed")]
+    WhitelistAlreadyClaimed,
+    #[msg("Arithmetic overflow")]
+    ArithmeticOverflow,
+    #[msg("Payout would leave the treasury below rent exemption")]
+    TreasuryUnderRent,
+    #[msg("Account was written by an older layout; migrate it first")]
+    UnsupportedAccountVersion,
+    #[msg("Report has no disputes to resolve")]
+    NoOutstandingDisputes,
+    #[msg("Report dispute has already been resolved")]
+    ReportResolved,
+    #[msg("Tier list must have 1 to 8 entries, matching across prices and durations")]
+    InvalidTierCount,
+    #[msg("Min update interval cannot be negative")]
+    InvalidUpdateInterval,
+    #[msg("Report was updated too recently")]
+    UpdateTooSoon,
+    #[msg("Amount exceeds the escrow balance")]
+    InsufficientEscrowBalance,
+    #[msg("Withdrawal would leave the escrow below rent exemption")]
+    EscrowUnderRent,
+    #[msg("Report has been deactivated")]
+    ReportInactive,
+    #[msg("Threshold must be between 1 and the number of signers (max 5), or 0 with no signers")]
+    InvalidThreshold,
+    #[msg("Signer set contains a duplicate")]
+    DuplicateSigner,
+    #[msg("Signer is not a member of the registry's signer set")]
+    NotASigner,
+    #[msg("Signer has already approved this report")]
+    AlreadyApproved,
+    #[msg("Pending report does not have enough approvals")]
+    ThresholdNotReached,
+    #[msg("Registry requires multisig submissions")]
+    MultisigRequired,
+    #[msg("Registry has no multisig signer set")]
+    MultisigNotConfigured,
+    #[msg("Refund window has closed")]
+    RefundWindowClosed,
+    #[msg("Refund window must be non-negative and refund mode 0, 1 or 2")]
+    InvalidRefundPolicy,
+    #[msg("Category must be between 0 and 5")]
+    InvalidCategory,
+    #[msg("Delegate has been revoked")]
+    DelegateRevoked,
+    #[msg("Subscriber cap reached")]
+    SubscriberCapReached,
+    #[msg("Promo prices must be below the regular tier prices")]
+    InvalidPromoPrice,
+    #[msg("Risk score is too high for the number of flags")]
+    InconsistentRiskData,
+    #[msg("Flag threshold exceeds the number of flags")]
+    InvalidFlagThreshold,
+    #[msg("Report quota for this period exceeded")]
+    ReportQuotaExceeded,
+    #[msg("Quota period must be positive")]
+    InvalidQuotaPeriod,
+    #[msg("TokenConsensus account does not match the report's mint")]
+    InvalidConsensusAccount,
+    #[msg("Invalid Merkle proof")]
+    InvalidProof,
+    #[msg("Already claimed under this whitelist root")]
+    AlreadyClaimed,
+    #[msg("Payment did not reach the recipient")]
+    PaymentNotReceived,
+    #[msg("Receipt already minted for this subscription")]
+    ReceiptAlreadyMinted,
+    #[msg("Inactivity timeout cannot be negative")]
+    InvalidInactivityTimeout,
+    #[msg("Admin has acted within the inactivity timeout")]
+    AdminStillActive,
+    #[msg("Top-up must be between 1 and MAX_TOPUP_DAYS days")]
+    InvalidTopupDays,
+    #[msg("Report is finalized and can no longer be changed")]
+    ReportFinalized,
+    #[msg("Watchlist is full")]
+    WatchlistFull,
+    #[msg("Report slot must be below MAX_REPORT_SLOTS")]
+    InvalidReportSlot,
+    #[msg("Report accounts must be passed once each, in ascending address order")]
+    DuplicateReportAccount,
+    #[msg("Late renewal penalty must be at most 10000 bps")]
+    InvalidLatePenalty,
+    #[msg("HIGH risk reports need a cosigner from the registry's signer set")]
+    CosignRequired,
+    #[msg("Subscribe cooldown must not be negative")]
+    InvalidSubscribeCooldown,
+    #[msg("Too soon after this wallet's last subscribe or cancel")]
+    ActionRateLimited,
+    #[msg("A coupon requires its code")]
+    InvalidCouponCode,
+    #[msg("Token report index account does not match the expected PDA")]
+    InvalidTokenIndexAccount,
+    #[msg("A revenue split is set; pass the revenue_split account")]
+    RevenueSplitRequired,
+    #[msg("Account data does not match any supported layout")]
+    UnsupportedAccountLayout,
+    #[msg("Pass both the old and the new account, or neither")]
+    MigrationAccountsMismatch,
+    #[msg("No recount in progress; pass first_page to start one")]
+    RecountNotStarted,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn put<T: AnchorSerialize>(data: &mut Vec<u8>, value: T) {
+        data.extend(value.try_to_vec().unwrap());
+    }
+
+    #[test]
+    fn migrates_legacy_report() {
+        let authority = Pubkey::new_unique();
+        let token_mint = Pubkey::new_unique();
+        let mut data = SafetyReport::DISCRIMINATOR.to_vec();
+        put(&mut data, authority);
+        put(&mut data, token_mint);
+        put(&mut data, 42u8);
+        put(&mut data, RiskLevel::Medium);
+        put(&mut data, 3u8); // flags_count
+        put(&mut data, String::from("Jupiter"));
+        put(&mut data, 1_700_000_000i64);
+        put(&mut data, 254u8);
+        data.resize(8 + LegacySafetyReport::INIT_SPACE, 0);
+
+        let report = decode_old_report(&data).unwrap();
+        assert_eq!(report.version, CURRENT_VERSION);
+        assert_eq!(report.authority, authority);
+        assert_eq!(report.token_mint, token_mint);
+        assert_eq!(report.risk_score, 42);
+        assert!(report.risk_level == RiskLevel::Medium);
+        assert_eq!((report.flags, report.flags_count), (0, 0));
+        assert_eq!(report.protocol_name, "Jupiter");
+        assert_eq!(report.created_at, 1_700_000_000);
+        assert_eq!(report.confidence, DEFAULT_CONFIDENCE);
+        assert!(report.active && !report.resolved);
+        assert_eq!(report.bump, 254);
+    }
+
+    /// A version 1 report body, with the fields later versions appended left off.
+    fn v1_report(version: u8, authority: Pubkey, token_mint: Pubkey) -> Vec<u8> {
+        let mut data = SafetyReport::DISCRIMINATOR.to_vec();
+        put(&mut data, version);
+        put(&mut data, authority);
+        put(&mut data, token_mint);
+        put(&mut data, 80u8);
+        put(&mut data, RiskLevel::Low);
+        put(&mut data, 1u32 << FLAG_MINT_AUTHORITY | 1 << FLAG_ACCOUNT_AGE);
+        put(&mut data, 2u8); // flags_count
+        put(&mut data, String::from("Jupiter"));
+        put(&mut data, String::from("ipfs://report"));
+        put(&mut data, 1_700_000_100i64); // timestamp
+        put(&mut data, 1_700_000_000i64); // created_at
+        put(&mut data, 4u32); // endorsements
+        put(&mut data, 1u32); // disputes
+        put(&mut data, 80u8); // confidence
+        put(&mut data, 2u8); // min_tier
+        put(&mut data, 253u8);
+        data
+    }
+
+    #[test]
+    fn migrates_v1_report() {
+        let authority = Pubkey::new_unique();
+        let token_mint = Pubkey::new_unique();
+        let mut data = v1_report(1, authority, token_mint);
+        data.resize(data.len() + 64, 0); // Padding up to the account's allocated size
+
+        let report = decode_old_report(&data).unwrap();
+        assert_eq!(report.version, CURRENT_VERSION);
+        assert_eq!(report.authority, authority);
+        assert_eq!(report.token_mint, token_mint);
+        assert_eq!(report.flags, 1u32 << FLAG_MINT_AUTHORITY | 1 << FLAG_ACCOUNT_AGE);
+        assert_eq!(report.report_uri, "ipfs://report");
+        assert_eq!((report.endorsements, report.disputes, report.confidence), (4, 1, 80));
+        assert_eq!(report.min_tier, 2);
+        assert!(report.active && !report.resolved);
+        assert_eq!(report.category, CATEGORY_UNKNOWN);
+        assert_eq!(report.bump, 253);
+    }
+
+    #[test]
+    fn migrates_appended_report_fields() {
+        let mut data = v1_report(5, Pubkey::new_unique(), Pubkey::new_unique());
+        put(&mut data, true); // resolved, version 2
+        put(&mut data, false); // active, version 5
+        data.resize(data.len() + 64, 0);
+
+        let report = decode_old_report(&data).unwrap();
+        assert!(report.resolved && !report.active);
+        assert_eq!(report.report_slot, 0);
+    }
+
+    #[test]
+    fn migrates_legacy_config_to_tiers() {
+        let admin = Pubkey::new_unique();
+        let mut data = SubscriptionConfig::DISCRIMINATOR.to_vec();
+        put(&mut data, admin);
+        put(&mut data, Pubkey::new_unique()); // treasury
+        for price in [1_000_000u64, 2_000_000, 3_000_000] {
+            put(&mut data, price);
+        }
+        put(&mut data, 30 * 86_400i64); // subscription_duration
+        put(&mut data, 7u64); // total_subscribers
+        put(&mut data, 9_000_000u64); // total_revenue
+        put(&mut data, 255u8);
+        assert_eq!(data.len(), 8 + LegacySubscriptionConfig::INIT_SPACE);
+
+        let config = decode_old_config(&data, 251).unwrap();
+        assert_eq!(config.version, CURRENT_VERSION);
+        assert_eq!(config.admin, admin);
+        assert_eq!(config.tier_prices, vec![1_000_000, 2_000_000, 3_000_000]);
+        assert_eq!(config.tier_durations, vec![30 * 86_400; 3]);
+        assert_eq!(config.tier_count, 3);
+        assert_eq!(config.tier_subscribers, vec![0; 3]);
+        assert_eq!(config.lifetime_price, u64::MAX);
+        assert_eq!((config.total_subscribers, config.total_revenue), (7, 9_000_000));
+        assert_eq!(config.token_treasury, config.treasury);
+        assert_eq!((config.treasury_bump, config.bump), (251, 255));
+    }
 }
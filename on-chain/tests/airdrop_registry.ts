@@ -18,6 +18,35 @@ describe("airdrop_registry", () => {
   let registryBump: number;
   let reportPda: PublicKey;
   let reportBump: number;
+  let reporterPda: PublicKey;
+  let historyPda: PublicKey;
+  let globalStatsPda: PublicKey;
+  let configPda: PublicKey;
+  let treasuryPda: PublicKey;
+
+  const DAY = 24 * 60 * 60;
+
+  const flagIndexPda = (bit: number) =>
+    PublicKey.findProgramAddressSync(
+      [Buffer.from("flag_index"), Buffer.from([bit])],
+      program.programId
+    )[0];
+  const consensusPda = (mint: PublicKey) =>
+    PublicKey.findProgramAddressSync(
+      [Buffer.from("consensus"), mint.toBuffer()],
+      program.programId
+    )[0];
+  const tokenIndexPda = (mint: PublicKey) =>
+    PublicKey.findProgramAddressSync(
+      [Buffer.from("token_index"), mint.toBuffer()],
+      program.programId
+    )[0];
+  const flagIndexAccounts = (bits: number[]) =>
+    bits.map((bit) => ({
+      pubkey: flagIndexPda(bit),
+      isWritable: true,
+      isSigner: false,
+    }));
 
   before(async () => {
     // Derive PDAs
@@ -34,6 +63,51 @@ describe("airdrop_registry", () => {
       ],
       program.programId
     );
+
+    [reporterPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("reporter"), authority.publicKey.toBuffer()],
+      program.programId
+    );
+
+    [historyPda] = PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("report_history"),
+        tokenMint.publicKey.toBuffer(),
+        authority.publicKey.toBuffer(),
+      ],
+      program.programId
+    );
+
+    [globalStatsPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("global_stats")],
+      program.programId
+    );
+
+    [configPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("subscription_config")],
+      program.programId
+    );
+
+    [treasuryPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("treasury")],
+      program.programId
+    );
+
+    // Report submissions read report quotas from the subscription config
+    await program.methods
+      .initializeSubscriptionConfig(
+        [1_000_000, 2_000_000, 3_000_000].map((p) => new anchor.BN(p)),
+        new anchor.BN(10_000_000),
+        [30 * DAY, 30 * DAY, 30 * DAY].map((d) => new anchor.BN(d)),
+        new anchor.BN(0)
+      )
+      .accounts({
+        subscriptionConfig: configPda,
+        treasury: treasuryPda,
+        admin: authority.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
   });
 
   it("Initializes the registry", async () => {
@@ -55,16 +129,41 @@ describe("airdrop_registry", () => {
     expect(registry.totalReports.toNumber()).to.equal(0);
   });
 
+  it("Initializes flag indexes", async () => {
+    for (const bit of [0, 1, 2]) {
+      await program.methods
+        .initializeFlagIndex(bit)
+        .accounts({
+          flagIndex: flagIndexPda(bit),
+          payer: authority.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+    }
+
+    const index = await program.account.flagIndex.fetch(flagIndexPda(1));
+    expect(index.flagBit).to.equal(1);
+    expect(index.tokenMints).to.have.length(0);
+  });
+
   it("Submits a safety report", async () => {
     const tx = await program.methods
-      .submitReport("TestProtocol", 85, 2, 3)
+      .submitReport("TestProtocol", 85, { low: {} }, 0b111, "ipfs://bafyTestReport", 0, 1, 0)
       .accounts({
         safetyReport: reportPda,
         registry: registryPda,
+        reporter: reporterPda,
+        globalStats: globalStatsPda,
+        consensus: consensusPda(tokenMint.publicKey),
+        tokenIndex: tokenIndexPda(tokenMint.publicKey),
+        subscriptionConfig: configPda,
+        subscription: null,
         tokenMint: tokenMint.publicKey,
         authority: authority.publicKey,
+        cosigner: null,
         systemProgram: SystemProgram.programId,
       })
+      .remainingAccounts(flagIndexAccounts([0, 1, 2]))
       .rpc();
 
     console.log("  Submit report tx:", tx);
@@ -77,31 +176,231 @@ describe("airdrop_registry", () => {
       tokenMint.publicKey.toBase58()
     );
     expect(report.riskScore).to.equal(85);
-    expect(report.riskLevel).to.equal(2);
+    expect(report.riskLevel).to.deep.equal({ low: {} });
+    expect(report.flags).to.equal(0b111);
     expect(report.flagsCount).to.equal(3);
     expect(report.protocolName).to.equal("TestProtocol");
+    expect(report.reportUri).to.equal("ipfs://bafyTestReport");
+    expect(report.active).to.equal(true);
+    expect(report.category).to.equal(1);
+    // Neutral until endorsed or disputed
+    expect(report.confidence).to.equal(50);
 
     // Check registry was updated
     const registry = await program.account.registry.fetch(registryPda);
     expect(registry.totalReports.toNumber()).to.equal(1);
+
+    // Reporter profile is created on the first report
+    const reporter = await program.account.reporter.fetch(reporterPda);
+    expect(reporter.reputation).to.equal(100);
+    expect(reporter.reportsSubmitted.toNumber()).to.equal(1);
+    expect(reporter.activeReports.toNumber()).to.equal(1);
+
+    const stats = await program.account.globalStats.fetch(globalStatsPda);
+    expect(stats.totalReports.toNumber()).to.equal(1);
+    expect(stats.scoreSum.toNumber()).to.equal(85);
+    expect(stats.lowReports.toNumber()).to.equal(1);
+
+    // The authority is listed for the token
+    const tokenIndex = await program.account.tokenReportIndex.fetch(
+      tokenIndexPda(tokenMint.publicKey)
+    );
+    expect(tokenIndex.authorities.map((a) => a.toBase58())).to.deep.equal([
+      authority.publicKey.toBase58(),
+    ]);
+
+    // Mint is listed under each of its flags
+    const index = await program.account.flagIndex.fetch(flagIndexPda(2));
+    expect(index.tokenMints.map((m) => m.toBase58())).to.include(
+      tokenMint.publicKey.toBase58()
+    );
   });
 
   it("Updates a safety report", async () => {
     const tx = await program.methods
-      .updateReport("TestProtocol v2", 92, 2, 1)
+      .updateReport("TestProtocol v2", 92, { low: {} }, 1, "", 1)
       .accounts({
         safetyReport: reportPda,
+        reportHistory: historyPda,
+        registry: registryPda,
+        globalStats: globalStatsPda,
+        consensus: consensusPda(tokenMint.publicKey),
         authority: authority.publicKey,
+        cosigner: null,
+        systemProgram: SystemProgram.programId,
       })
+      .remainingAccounts(flagIndexAccounts([1, 2]))
       .rpc();
 
     console.log("  Update report tx:", tx);
 
     const report = await program.account.safetyReport.fetch(reportPda);
     expect(report.riskScore).to.equal(92);
-    expect(report.riskLevel).to.equal(2);
+    expect(report.riskLevel).to.deep.equal({ low: {} });
+    expect(report.flags).to.equal(1);
     expect(report.flagsCount).to.equal(1);
     expect(report.protocolName).to.equal("TestProtocol v2");
+    expect(report.reportUri).to.equal("");
+    // Creation time survives updates
+    expect(report.createdAt.toNumber()).to.be.at.most(report.timestamp.toNumber());
+
+    // Pre-update values are archived
+    const history = await program.account.reportHistory.fetch(historyPda);
+    expect(history.totalVersions.toNumber()).to.equal(1);
+    expect(history.entries[0].riskScore).to.equal(85);
+
+    // The old score is swapped out of the running sum
+    const stats = await program.account.globalStats.fetch(globalStatsPda);
+    expect(stats.totalReports.toNumber()).to.equal(1);
+    expect(stats.scoreSum.toNumber()).to.equal(92);
+
+    const consensus = await program.account.tokenConsensus.fetch(
+      consensusPda(tokenMint.publicKey)
+    );
+    expect(consensus.reportCount.toNumber()).to.equal(1);
+    expect(consensus.sumScores.toNumber()).to.equal(92);
+
+    // Cleared flags drop the mint from their index
+    const index = await program.account.flagIndex.fetch(flagIndexPda(2));
+    expect(index.tokenMints).to.have.length(0);
+  });
+
+  it("Updates only the risk score", async () => {
+    await program.methods
+      .updateRiskScore(88)
+      .accounts({
+        safetyReport: reportPda,
+        reportHistory: historyPda,
+        registry: registryPda,
+        globalStats: globalStatsPda,
+        consensus: consensusPda(tokenMint.publicKey),
+        authority: authority.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+
+    const report = await program.account.safetyReport.fetch(reportPda);
+    expect(report.riskScore).to.equal(88);
+    expect(report.riskLevel).to.deep.equal({ low: {} });
+    expect(report.flags).to.equal(1);
+    expect(report.protocolName).to.equal("TestProtocol v2");
+
+    const history = await program.account.reportHistory.fetch(historyPda);
+    expect(history.totalVersions.toNumber()).to.equal(2);
+
+    const stats = await program.account.globalStats.fetch(globalStatsPda);
+    expect(stats.scoreSum.toNumber()).to.equal(88);
+  });
+
+  it("Returns a safe verdict under the registry thresholds", async () => {
+    const accounts = { safetyReport: reportPda, registry: registryPda };
+    let verdict = await program.methods.getVerdict().accounts(accounts).view();
+    expect(verdict.safe).to.equal(true);
+
+    await program.methods
+      .setSafeThresholds(90, 2)
+      .accounts({
+        subscriptionConfig: configPda,
+        registry: registryPda,
+        admin: authority.publicKey,
+      })
+      .rpc();
+    verdict = await program.methods.getVerdict().accounts(accounts).view();
+    expect(verdict.safe).to.equal(false);
+
+    await program.methods
+      .setSafeThresholds(70, 2)
+      .accounts({
+        subscriptionConfig: configPda,
+        registry: registryPda,
+        admin: authority.publicKey,
+      })
+      .rpc();
+  });
+
+  it("Stores a second report for the same token in another slot", async () => {
+    const [slotReportPda] = PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("safety_report"),
+        tokenMint.publicKey.toBuffer(),
+        authority.publicKey.toBuffer(),
+        Buffer.from([1]),
+      ],
+      program.programId
+    );
+
+    await program.methods
+      .submitReport("TestProtocol dynamic", 70, { medium: {} }, 0, "", 0, 1, 1)
+      .accounts({
+        safetyReport: slotReportPda,
+        registry: registryPda,
+        reporter: reporterPda,
+        globalStats: globalStatsPda,
+        consensus: consensusPda(tokenMint.publicKey),
+        tokenIndex: tokenIndexPda(tokenMint.publicKey),
+        subscriptionConfig: configPda,
+        subscription: null,
+        tokenMint: tokenMint.publicKey,
+        authority: authority.publicKey,
+        cosigner: null,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+
+    const slotReport = await program.account.safetyReport.fetch(slotReportPda);
+    expect(slotReport.reportSlot).to.equal(1);
+    expect(slotReport.riskScore).to.equal(70);
+
+    // The default slot's report is untouched
+    const report = await program.account.safetyReport.fetch(reportPda);
+    expect(report.reportSlot).to.equal(0);
+    expect(report.riskScore).to.equal(88);
+
+    // The authority is listed once with both slots, and counted once in consensus
+    const tokenIndex = await program.account.tokenReportIndex.fetch(
+      tokenIndexPda(tokenMint.publicKey)
+    );
+    expect(tokenIndex.authorities.length).to.equal(1);
+    expect(tokenIndex.slots).to.deep.equal([0b11]);
+    const consensus = await program.account.tokenConsensus.fetch(
+      consensusPda(tokenMint.publicKey)
+    );
+    expect(consensus.reportCount.toNumber()).to.equal(1);
+  });
+
+  it("Stores a max-length protocol name as submitted", async () => {
+    const mint = Keypair.generate();
+    const [namedReportPda] = PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("safety_report"),
+        mint.publicKey.toBuffer(),
+        authority.publicKey.toBuffer(),
+      ],
+      program.programId
+    );
+    const name = "Protocol-" + "x".repeat(55); // 64 bytes, the cap
+
+    await program.methods
+      .submitReport(name, 60, { medium: {} }, 0, "ar://report", 0, 2, 0)
+      .accounts({
+        safetyReport: namedReportPda,
+        registry: registryPda,
+        reporter: reporterPda,
+        globalStats: globalStatsPda,
+        consensus: consensusPda(mint.publicKey),
+        tokenIndex: tokenIndexPda(mint.publicKey),
+        subscriptionConfig: configPda,
+        subscription: null,
+        tokenMint: mint.publicKey,
+        authority: authority.publicKey,
+        cosigner: null,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+
+    const report = await program.account.safetyReport.fetch(namedReportPda);
+    expect(report.protocolName).to.equal(name);
+    expect(report.reportUri).to.equal("ar://report");
   });
 
   it("Rejects invalid risk score (> 100)", async () => {
@@ -117,12 +416,19 @@ describe("airdrop_registry", () => {
 
     try {
       await program.methods
-        .submitReport("BadScore", 101, 0, 5)
+        .submitReport("BadScore", 101, { high: {} }, 0b11111, "", 0, 0, 0)
         .accounts({
           safetyReport: anotherReportPda,
           registry: registryPda,
+          reporter: reporterPda,
+          globalStats: globalStatsPda,
+          consensus: consensusPda(anotherMint.publicKey),
+          tokenIndex: tokenIndexPda(anotherMint.publicKey),
+          subscriptionConfig: configPda,
+          subscription: null,
           tokenMint: anotherMint.publicKey,
           authority: authority.publicKey,
+          cosigner: null,
           systemProgram: SystemProgram.programId,
         })
         .rpc();
@@ -132,7 +438,42 @@ describe("airdrop_registry", () => {
     }
   });
 
-  it("Rejects invalid risk level (> 2)", async () => {
+  it("Rejects reserved flag bits", async () => {
+    const anotherMint = Keypair.generate();
+    const [anotherReportPda] = PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("safety_report"),
+        anotherMint.publicKey.toBuffer(),
+        authority.publicKey.toBuffer(),
+      ],
+      program.programId
+    );
+
+    try {
+      await program.methods
+        .submitReport("BadFlags", 50, { medium: {} }, 1 << 20, "", 0, 0, 0)
+        .accounts({
+          safetyReport: anotherReportPda,
+          registry: registryPda,
+          reporter: reporterPda,
+          globalStats: globalStatsPda,
+          consensus: consensusPda(anotherMint.publicKey),
+          tokenIndex: tokenIndexPda(anotherMint.publicKey),
+          subscriptionConfig: configPda,
+          subscription: null,
+          tokenMint: anotherMint.publicKey,
+          authority: authority.publicKey,
+          cosigner: null,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+      expect.fail("Should have thrown an error");
+    } catch (err: any) {
+      expect(err.error.errorCode.code).to.equal("InvalidFlags");
+    }
+  });
+
+  it("Rejects a HIGH risk report without a cosigner", async () => {
     const anotherMint = Keypair.generate();
     const [anotherReportPda] = PublicKey.findProgramAddressSync(
       [
@@ -145,18 +486,612 @@ describe("airdrop_registry", () => {
 
     try {
       await program.methods
-        .submitReport("BadLevel", 50, 3, 5)
+        .submitReport("Takedown", 10, { high: {} }, 0, "", 0, 0, 0)
         .accounts({
           safetyReport: anotherReportPda,
           registry: registryPda,
+          reporter: reporterPda,
+          globalStats: globalStatsPda,
+          consensus: consensusPda(anotherMint.publicKey),
+          tokenIndex: tokenIndexPda(anotherMint.publicKey),
+          subscriptionConfig: configPda,
+          subscription: null,
           tokenMint: anotherMint.publicKey,
           authority: authority.publicKey,
+          cosigner: null,
           systemProgram: SystemProgram.programId,
         })
         .rpc();
       expect.fail("Should have thrown an error");
     } catch (err: any) {
-      expect(err.error.errorCode.code).to.equal("InvalidRiskLevel");
+      expect(err.error.errorCode.code).to.equal("CosignRequired");
     }
   });
+
+  it("Rejects raising a report to HIGH without a cosigner", async () => {
+    try {
+      await program.methods
+        .updateReport("TestProtocol v2", 10, { high: {} }, 1, "", 1)
+        .accounts({
+          safetyReport: reportPda,
+          reportHistory: historyPda,
+          registry: registryPda,
+          globalStats: globalStatsPda,
+          consensus: consensusPda(tokenMint.publicKey),
+          authority: authority.publicKey,
+          cosigner: null,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+      expect.fail("Should have thrown an error");
+    } catch (err: any) {
+      expect(err.error.errorCode.code).to.equal("CosignRequired");
+    }
+  });
+
+  it("Rejects updates while the registry is paused", async () => {
+    await program.methods
+      .setPaused(true)
+      .accounts({ registry: registryPda, authority: authority.publicKey })
+      .rpc();
+
+    try {
+      await program.methods
+        .updateReport("TestProtocol v3", 90, { low: {} }, 1, "", 1)
+        .accounts({
+          safetyReport: reportPda,
+          reportHistory: historyPda,
+          registry: registryPda,
+          globalStats: globalStatsPda,
+          consensus: consensusPda(tokenMint.publicKey),
+          authority: authority.publicKey,
+          cosigner: null,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+      expect.fail("Should have thrown an error");
+    } catch (err: any) {
+      expect(err.error.errorCode.code).to.equal("RegistryPaused");
+    } finally {
+      await program.methods
+        .setPaused(false)
+        .accounts({ registry: registryPda, authority: authority.publicKey })
+        .rpc();
+    }
+  });
+
+  it("Submits a batch of reports", async () => {
+    const mints = [Keypair.generate(), Keypair.generate()];
+    const reportPdas = mints.map(
+      (mint) =>
+        PublicKey.findProgramAddressSync(
+          [
+            Buffer.from("safety_report"),
+            mint.publicKey.toBuffer(),
+            authority.publicKey.toBuffer(),
+          ],
+          program.programId
+        )[0]
+    );
+    const before = await program.account.registry.fetch(registryPda);
+
+    await program.methods
+      .submitReportsBatch(
+        mints.map((mint, i) => ({
+          tokenMint: mint.publicKey,
+          protocolName: `BatchProtocol${i}`,
+          riskScore: 70 + i,
+          riskLevel: { medium: {} },
+          flags: i,
+          reportUri: "",
+          minTier: 0,
+          category: 3,
+        }))
+      )
+      .accounts({
+        registry: registryPda,
+        reporter: reporterPda,
+        globalStats: globalStatsPda,
+        subscriptionConfig: configPda,
+        subscription: null,
+        authority: authority.publicKey,
+        cosigner: null,
+        systemProgram: SystemProgram.programId,
+      })
+      .remainingAccounts(
+        reportPdas
+          .flatMap((pubkey, i) => [
+            { pubkey, isWritable: true, isSigner: false },
+            { pubkey: consensusPda(mints[i].publicKey), isWritable: true, isSigner: false },
+            { pubkey: tokenIndexPda(mints[i].publicKey), isWritable: true, isSigner: false },
+          ])
+          .concat(flagIndexAccounts([0]))
+      )
+      .rpc();
+
+    const report = await program.account.safetyReport.fetch(reportPdas[1]);
+    expect(report.protocolName).to.equal("BatchProtocol1");
+    expect(report.riskScore).to.equal(71);
+
+    const index = await program.account.flagIndex.fetch(flagIndexPda(0));
+    expect(index.tokenMints.map((m) => m.toBase58())).to.include(
+      mints[1].publicKey.toBase58()
+    );
+
+    const after = await program.account.registry.fetch(registryPda);
+    expect(after.totalReports.toNumber()).to.equal(
+      before.totalReports.toNumber() + 2
+    );
+  });
+
+  it("Upserts a report, counting only its creation", async () => {
+    const mint = Keypair.generate();
+    const [upsertReportPda] = PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("safety_report"),
+        mint.publicKey.toBuffer(),
+        authority.publicKey.toBuffer(),
+      ],
+      program.programId
+    );
+    const [upsertHistoryPda] = PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("report_history"),
+        mint.publicKey.toBuffer(),
+        authority.publicKey.toBuffer(),
+      ],
+      program.programId
+    );
+    const upsert = (riskScore: number) =>
+      program.methods
+        .upsertReport({
+          tokenMint: mint.publicKey,
+          protocolName: "UpsertProtocol",
+          riskScore,
+          riskLevel: { medium: {} },
+          flags: 0,
+          reportUri: "",
+          minTier: 0,
+          category: 1,
+        })
+        .accounts({
+          safetyReport: upsertReportPda,
+          reportHistory: upsertHistoryPda,
+          registry: registryPda,
+          reporter: reporterPda,
+          globalStats: globalStatsPda,
+          consensus: consensusPda(mint.publicKey),
+          tokenIndex: tokenIndexPda(mint.publicKey),
+          subscriptionConfig: configPda,
+          subscription: null,
+          authority: authority.publicKey,
+          cosigner: null,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+    const before = await program.account.registry.fetch(registryPda);
+
+    await upsert(60);
+    await upsert(65);
+
+    const report = await program.account.safetyReport.fetch(upsertReportPda);
+    expect(report.riskScore).to.equal(65);
+    const history = await program.account.reportHistory.fetch(upsertHistoryPda);
+    expect(history.entries[0].riskScore).to.equal(60);
+
+    const after = await program.account.registry.fetch(registryPda);
+    expect(after.totalReports.toNumber()).to.equal(
+      before.totalReports.toNumber() + 1
+    );
+  });
+
+  it("Rejects self-endorsement", async () => {
+    const [endorsementPda] = PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("endorsement"),
+        reportPda.toBuffer(),
+        authority.publicKey.toBuffer(),
+      ],
+      program.programId
+    );
+
+    try {
+      await program.methods
+        .endorseReport(true)
+        .accounts({
+          safetyReport: reportPda,
+          endorsement: endorsementPda,
+          endorserRegistry: registryPda,
+          endorser: authority.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+      expect.fail("Should have thrown an error");
+    } catch (err: any) {
+      expect(err.error.errorCode.code).to.equal("SelfEndorsement");
+    }
+  });
+
+  it("Finalizes a report and rejects later updates", async () => {
+    await program.methods
+      .finalizeReport()
+      .accounts({ safetyReport: reportPda, authority: authority.publicKey })
+      .rpc();
+
+    const report = await program.account.safetyReport.fetch(reportPda);
+    expect(report.finalized).to.equal(true);
+
+    try {
+      await program.methods
+        .updateRiskScore(10)
+        .accounts({
+          safetyReport: reportPda,
+          reportHistory: historyPda,
+          registry: registryPda,
+          globalStats: globalStatsPda,
+          consensus: consensusPda(tokenMint.publicKey),
+          authority: authority.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+      expect.fail("Should have thrown an error");
+    } catch (err: any) {
+      expect(err.error.errorCode.code).to.equal("ReportFinalized");
+    }
+  });
+
+  it("Rejects a recount that lists a report twice", async () => {
+    try {
+      await program.methods
+        .recomputeTotalReports(true, true)
+        .accounts({
+          registry: registryPda,
+          reporter: reporterPda,
+          recount: PublicKey.findProgramAddressSync(
+            [Buffer.from("recount"), registryPda.toBuffer()],
+            program.programId
+          )[0],
+          authority: authority.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .remainingAccounts([
+          { pubkey: reportPda, isSigner: false, isWritable: false },
+          { pubkey: reportPda, isSigner: false, isWritable: false },
+        ])
+        .rpc();
+      expect.fail("Should have thrown an error");
+    } catch (err: any) {
+      expect(err.error.errorCode.code).to.equal("DuplicateReportAccount");
+    }
+  });
+
+  describe("subscriptions", () => {
+    const user = authority.publicKey;
+    let subscriptionPda: PublicKey;
+    let subHistoryPda: PublicKey;
+    let subscriberMetaPda: PublicKey;
+
+    before(async () => {
+      [subscriptionPda] = PublicKey.findProgramAddressSync(
+        [Buffer.from("subscription"), user.toBuffer()],
+        program.programId
+      );
+      [subHistoryPda] = PublicKey.findProgramAddressSync(
+        [Buffer.from("sub_history"), user.toBuffer()],
+        program.programId
+      );
+      [subscriberMetaPda] = PublicKey.findProgramAddressSync(
+        [Buffer.from("subscriber_meta"), user.toBuffer()],
+        program.programId
+      );
+    });
+
+    it("Rejects an invalid tier without moving funds or writing state", async () => {
+      const userBefore = await provider.connection.getBalance(user);
+      const treasuryBefore = await provider.connection.getBalance(treasuryPda);
+
+      try {
+        await program.methods
+          .subscribe(9, null, null, new anchor.BN("18446744073709551615"))
+          .accounts({
+            subscription: subscriptionPda,
+            subscriberMeta: subscriberMetaPda,
+            subscriptionHistory: subHistoryPda,
+            subscriptionConfig: configPda,
+            treasury: treasuryPda,
+            referral: null,
+            referrerWallet: null,
+            coupon: null,
+            revenueSplit: null,
+            user,
+            systemProgram: SystemProgram.programId,
+          })
+          .rpc();
+        expect.fail("Should have thrown an error");
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal("InvalidTier");
+      }
+
+      expect(await provider.connection.getBalance(user)).to.equal(userBefore);
+      expect(await provider.connection.getBalance(treasuryPda)).to.equal(
+        treasuryBefore
+      );
+      expect(
+        await program.account.subscription.fetchNullable(subscriptionPda)
+      ).to.equal(null);
+
+      const config = await program.account.subscriptionConfig.fetch(configPda);
+      expect(config.totalSubscribers.toNumber()).to.equal(0);
+      expect(config.totalRevenue.toNumber()).to.equal(0);
+    });
+
+    it("Subscribes annually at the configured discount", async () => {
+      try {
+        await program.methods
+          .updateAnnualDiscount(10_000)
+          .accounts({ subscriptionConfig: configPda, admin: user })
+          .rpc();
+        expect.fail("Should have thrown an error");
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal("InvalidDiscount");
+      }
+
+      await program.methods
+        .updateAnnualDiscount(2_000)
+        .accounts({ subscriptionConfig: configPda, admin: user })
+        .rpc();
+
+      const treasuryBefore = await provider.connection.getBalance(treasuryPda);
+      await program.methods
+        .subscribeAnnual(1, new anchor.BN("18446744073709551615"))
+        .accounts({
+          subscription: subscriptionPda,
+          subscriberMeta: subscriberMetaPda,
+          subscriptionHistory: subHistoryPda,
+          subscriptionConfig: configPda,
+          treasury: treasuryPda,
+          user,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+
+      // 12 cycles of 1_000_000 lamports, 20% off
+      const price = 9_600_000;
+      expect(await provider.connection.getBalance(treasuryPda)).to.equal(
+        treasuryBefore + price
+      );
+      const subscription = await program.account.subscription.fetch(subscriptionPda);
+      expect(subscription.tier).to.equal(1);
+      expect(subscription.totalPaid.toNumber()).to.equal(price);
+      expect(
+        subscription.expiresAt.toNumber() - subscription.createdAt.toNumber()
+      ).to.equal(12 * 30 * DAY);
+      expect(subscription.firstSubscribedAt.toNumber()).to.equal(
+        subscription.createdAt.toNumber()
+      );
+      expect(subscription.totalActiveSeconds.toNumber()).to.equal(0);
+    });
+
+    it("Rate-limits a cancel right after subscribing", async () => {
+      await program.methods
+        .updateSubscribeCooldown(new anchor.BN(DAY))
+        .accounts({ subscriptionConfig: configPda, admin: user })
+        .rpc();
+
+      try {
+        await program.methods
+          .cancelSubscription()
+          .accounts({
+            subscription: subscriptionPda,
+            subscriberMeta: subscriberMetaPda,
+            subscriptionHistory: subHistoryPda,
+            subscriptionConfig: configPda,
+            treasury: treasuryPda,
+            user,
+            systemProgram: SystemProgram.programId,
+          })
+          .rpc();
+        expect.fail("Should have thrown an error");
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal("ActionRateLimited");
+      }
+
+      await program.methods
+        .updateSubscribeCooldown(new anchor.BN(0))
+        .accounts({ subscriptionConfig: configPda, admin: user })
+        .rpc();
+    });
+
+    describe("crank_renew", () => {
+      const escrowPda = (owner: PublicKey) =>
+        PublicKey.findProgramAddressSync(
+          [Buffer.from("escrow"), owner.toBuffer()],
+          program.programId
+        )[0];
+      const subscriptionPdaOf = (owner: PublicKey) =>
+        PublicKey.findProgramAddressSync(
+          [Buffer.from("subscription"), owner.toBuffer()],
+          program.programId
+        )[0];
+
+      // A fresh wallet with an expired Pro trial, auto-renew on and `escrow`
+      // lamports in its escrow
+      const expiredTrial = async (escrow: number) => {
+        const owner = Keypair.generate();
+        await provider.sendAndConfirm(
+          new anchor.web3.Transaction().add(
+            SystemProgram.transfer({
+              fromPubkey: user,
+              toPubkey: owner.publicKey,
+              lamports: 100_000_000,
+            })
+          )
+        );
+        await program.methods
+          .startTrial()
+          .accounts({
+            subscription: subscriptionPdaOf(owner.publicKey),
+            trialRecord: PublicKey.findProgramAddressSync(
+              [Buffer.from("trial"), owner.publicKey.toBuffer()],
+              program.programId
+            )[0],
+            subscriptionConfig: configPda,
+            user: owner.publicKey,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([owner])
+          .rpc();
+        await program.methods
+          .setAutoRenew(true)
+          .accounts({ subscription: subscriptionPdaOf(owner.publicKey), user: owner.publicKey })
+          .signers([owner])
+          .rpc();
+        await program.methods
+          .fundEscrow(new anchor.BN(escrow))
+          .accounts({
+            escrow: escrowPda(owner.publicKey),
+            subscriptionConfig: configPda,
+            user: owner.publicKey,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([owner])
+          .rpc();
+        return owner.publicKey;
+      };
+
+      const crank = (owner: PublicKey) =>
+        program.methods
+          .crankRenew()
+          .accounts({
+            subscription: subscriptionPdaOf(owner),
+            subscriptionConfig: configPda,
+            escrow: escrowPda(owner),
+            treasury: treasuryPda,
+            systemProgram: SystemProgram.programId,
+          })
+          .rpc();
+
+      let downgraded: PublicKey;
+      let unaffordable: PublicKey;
+
+      before(async () => {
+        await program.methods
+          .updateTrialDuration(new anchor.BN(1))
+          .accounts({ subscriptionConfig: configPda, admin: user })
+          .rpc();
+        downgraded = await expiredTrial(1_500_000);
+        unaffordable = await expiredTrial(500_000);
+        await program.methods
+          .updateTrialDuration(new anchor.BN(7 * DAY))
+          .accounts({ subscriptionConfig: configPda, admin: user })
+          .rpc();
+        // Let both one-second trials expire
+        await new Promise((resolve) => setTimeout(resolve, 3_000));
+      });
+
+      it("Renews at the highest tier the escrow covers", async () => {
+        await crank(downgraded);
+
+        // Pro costs 2_000_000, so the 1_500_000 escrow renews Basic
+        const subscription = await program.account.subscription.fetch(
+          subscriptionPdaOf(downgraded)
+        );
+        expect(subscription.tier).to.equal(1);
+        expect(subscription.isTrial).to.equal(false);
+        expect(subscription.autoRenew).to.equal(true);
+        const escrow = await program.account.escrow.fetch(escrowPda(downgraded));
+        expect(escrow.balance.toNumber()).to.equal(500_000);
+      });
+
+      it("Switches auto-renew off when the escrow covers no tier", async () => {
+        await crank(unaffordable);
+
+        const subscription = await program.account.subscription.fetch(
+          subscriptionPdaOf(unaffordable)
+        );
+        expect(subscription.autoRenew).to.equal(false);
+        expect(subscription.tier).to.equal(2);
+        const escrow = await program.account.escrow.fetch(escrowPda(unaffordable));
+        expect(escrow.balance.toNumber()).to.equal(500_000);
+      });
+    });
+
+    it("Requires the revenue split once it is set", async () => {
+      const recipients = [Keypair.generate(), Keypair.generate()];
+      // Fund both wallets to rent exemption so they can take small shares
+      const fund = new anchor.web3.Transaction().add(
+        ...recipients.map((r) =>
+          SystemProgram.transfer({
+            fromPubkey: user,
+            toPubkey: r.publicKey,
+            lamports: 1_000_000,
+          })
+        )
+      );
+      await provider.sendAndConfirm(fund);
+
+      const [revenueSplitPda] = PublicKey.findProgramAddressSync(
+        [Buffer.from("revenue_split")],
+        program.programId
+      );
+      await program.methods
+        .setRevenueSplit([
+          { recipient: recipients[0].publicKey, bps: 7_000 },
+          { recipient: recipients[1].publicKey, bps: 3_000 },
+        ])
+        .accounts({
+          subscriptionConfig: configPda,
+          revenueSplit: revenueSplitPda,
+          admin: user,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+
+      const renewAccounts = (revenueSplit: PublicKey | null) => ({
+        subscription: subscriptionPda,
+        subscriptionHistory: subHistoryPda,
+        subscriptionConfig: configPda,
+        treasury: treasuryPda,
+        revenueSplit,
+        user,
+        systemProgram: SystemProgram.programId,
+      });
+      try {
+        await program.methods
+          .renewSubscription(1, new anchor.BN("18446744073709551615"))
+          .accounts(renewAccounts(null))
+          .rpc();
+        expect.fail("Should have thrown an error");
+      } catch (err: any) {
+        expect(err.error.errorCode.code).to.equal("RevenueSplitRequired");
+      }
+
+      const treasuryBefore = await provider.connection.getBalance(treasuryPda);
+      const before = await Promise.all(
+        recipients.map((r) => provider.connection.getBalance(r.publicKey))
+      );
+      await program.methods
+        .renewSubscription(1, new anchor.BN("18446744073709551615"))
+        .accounts(renewAccounts(revenueSplitPda))
+        .remainingAccounts(
+          recipients.map((r) => ({
+            pubkey: r.publicKey,
+            isWritable: true,
+            isSigner: false,
+          }))
+        )
+        .rpc();
+
+      // Tier 1 costs 1_000_000 lamports, split 70/30
+      expect(await provider.connection.getBalance(recipients[0].publicKey)).to.equal(
+        before[0] + 700_000
+      );
+      expect(await provider.connection.getBalance(recipients[1].publicKey)).to.equal(
+        before[1] + 300_000
+      );
+      expect(await provider.connection.getBalance(treasuryPda)).to.equal(
+        treasuryBefore
+      );
+    });
+  });
 });